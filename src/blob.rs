@@ -0,0 +1,202 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: blob.rs
+ */
+extern crate sqlite3_sys;
+
+use std::ffi::c_void;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::ptr::{null_mut, NonNull};
+
+use libc::c_int;
+use sqlite3_sys::{sqlite3,
+                  sqlite3_blob,
+                  sqlite3_blob_bytes,
+                  sqlite3_blob_close,
+                  sqlite3_blob_open,
+                  sqlite3_blob_read,
+                  sqlite3_blob_reopen,
+                  sqlite3_blob_write,
+                  SQLITE_OK};
+
+use crate::db::SQLite;
+use crate::error::{Error, Result};
+use crate::ffi::to_cstring;
+
+/// Incremental I/O on one blob (column value of one row).
+///
+/// Handle is opened by SQLite::open_blob and implements Read, Write
+/// and Seek, so large blobs can be streamed (e.g. with io::copy)
+/// without loading them into memory. Size of the blob can't be changed,
+/// space for new data is reserved with zeroblob (see 'StoreRef::add_zeroblob').
+/// When the row is changed or deleted, the handle expires and further
+/// reads and writes fail.
+pub struct BlobHandle<'conn> {
+    blob: NonNull<sqlite3_blob>,
+    db: *mut sqlite3,
+    size: usize,
+    pos: usize,
+    _db: PhantomData<&'conn SQLite>,
+}
+
+impl<'conn> BlobHandle<'conn> {
+    /// Opens blob of the column in the row of the table.
+    pub(crate) fn open(db: *mut sqlite3, database: &str, table: &str, column: &str, rowid: i64, read_only: bool) -> Result<BlobHandle<'conn>> {
+        let (database, table, column) = (to_cstring(database)?, to_cstring(table)?, to_cstring(column)?);
+        let mut blob = null_mut();
+        let stat = unsafe {
+            sqlite3_blob_open(
+                db,
+                database.as_ptr(),
+                table.as_ptr(),
+                column.as_ptr(),
+                rowid,
+                !read_only as c_int,
+                &mut blob,
+            )
+        };
+        match (stat, NonNull::new(blob)) {
+            (SQLITE_OK, Some(blob)) => {
+                let size = unsafe { sqlite3_blob_bytes(blob.as_ptr()) as usize };
+                Ok(BlobHandle { blob, db, size, pos: 0, _db: PhantomData })
+            }
+            _ => Err(Error::from_db(db)),
+        }
+    }
+
+    /**** len ******************************************************/
+
+    /// Returns size of the blob in bytes.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /**** is_empty *************************************************/
+
+    /// Returns true when the blob has no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /**** reopen ***************************************************/
+
+    /// Moves the handle to the blob of other row of the same table
+    /// (faster than opening new handle). Position is set to the start.
+    pub fn reopen(&mut self, rowid: i64) -> Result<()> {
+        match unsafe { sqlite3_blob_reopen(self.blob.as_ptr(), rowid) } {
+            SQLITE_OK => {
+                self.size = unsafe { sqlite3_blob_bytes(self.blob.as_ptr()) as usize };
+                self.pos = 0;
+                Ok(())
+            }
+            _ => Err(Error::from_db(self.db)),
+        }
+    }
+
+    /**** close ****************************************************/
+
+    /// Closes the handle and reports error of the last write (if any).
+    pub fn close(self) -> Result<()> {
+        let (blob, db) = (self.blob.as_ptr(), self.db);
+        std::mem::forget(self);
+        match unsafe { sqlite3_blob_close(blob) } {
+            SQLITE_OK => Ok(()),
+            _ => Err(Error::from_db(db)),
+        }
+    }
+
+    /// Number of bytes which can be read or written from current position.
+    fn available(&self, wanted: usize) -> usize {
+        wanted.min(self.size.saturating_sub(self.pos))
+    }
+
+    fn io_error(&self) -> io::Error {
+        io::Error::other(Error::from_db(self.db))
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                  R e a d / W r i t e / S e e k                    *
+*                                                                   *
+********************************************************************/
+
+impl Read for BlobHandle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.available(buf.len());
+        if n == 0 {
+            return Ok(0);
+        }
+        let stat = unsafe {
+            sqlite3_blob_read(self.blob.as_ptr(), buf.as_mut_ptr() as *mut c_void, n as c_int, self.pos as c_int)
+        };
+        match stat {
+            SQLITE_OK => {
+                self.pos += n;
+                Ok(n)
+            }
+            _ => Err(self.io_error()),
+        }
+    }
+}
+
+/// Writes past the end of the blob write nothing (blob can't grow),
+/// so 'write_all' of too much data ends with ErrorKind::WriteZero.
+impl Write for BlobHandle<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.available(buf.len());
+        if n == 0 {
+            return Ok(0);
+        }
+        let stat = unsafe {
+            sqlite3_blob_write(self.blob.as_ptr(), buf.as_ptr() as *const c_void, n as c_int, self.pos as c_int)
+        };
+        match stat {
+            SQLITE_OK => {
+                self.pos += n;
+                Ok(n)
+            }
+            _ => Err(self.io_error()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for BlobHandle<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset as i64),
+            SeekFrom::End(offset) => (self.size as i64).checked_add(offset),
+            SeekFrom::Current(offset) => (self.pos as i64).checked_add(offset),
+        };
+        match pos {
+            Some(pos) if pos >= 0 => {
+                self.pos = pos as usize;
+                Ok(self.pos as u64)
+            }
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                       D r o p   T r a i t                         *
+*                                                                   *
+********************************************************************/
+
+impl Drop for BlobHandle<'_> {
+    fn drop(&mut self) {
+        // Errors can't be reported from drop, use 'close' to check them.
+        unsafe { sqlite3_blob_close(self.blob.as_ptr()) };
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: cache.rs
+ */
+use fxhash::FxHashMap;
+
+use crate::error::Result;
+use crate::ffi::StmtHandle;
+
+/// Default number of statements kept in the cache.
+pub const DEFAULT_CAPACITY: usize = 16;
+
+/// Statistics of the prepared statements cache.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct CacheStats {
+    /// Number of lookups which found prepared statement.
+    pub hits: u64,
+    /// Number of lookups which required statement preparation.
+    pub misses: u64,
+    /// Number of statements finalized because cache was full.
+    pub evictions: u64,
+    /// Number of statements in the cache.
+    pub size: usize,
+    /// Maximal number of statements in the cache.
+    pub capacity: usize,
+}
+
+/// LRU cache of prepared statements keyed by full SQL text.
+/// Cache owns the statements, they are finalized on eviction and on clear.
+pub(crate) struct StatementCache {
+    entries: FxHashMap<String, Entry>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+struct Entry {
+    stmt: StmtHandle,
+    last_used: u64,
+}
+
+impl StatementCache {
+    pub(crate) fn with_capacity(capacity: usize) -> StatementCache {
+        StatementCache {
+            entries: FxHashMap::default(),
+            tick: 0,
+            stats: CacheStats { capacity, ..CacheStats::default() },
+        }
+    }
+
+    /**** get_or_prepare *****************************************/
+
+    /// Returns statement prepared for the query. Statement missing
+    /// in the cache is prepared with 'prepare' and the cache takes
+    /// ownership of it. When the cache is full the least recently
+    /// used statement is finalized.
+    pub(crate) fn get_or_prepare<F>(&mut self, query: &str, prepare: F) -> Result<&StmtHandle>
+        where F: FnOnce() -> Result<StmtHandle>
+    {
+        self.tick += 1;
+        match self.entries.contains_key(query) {
+            true => self.stats.hits += 1,
+            _ => {
+                self.stats.misses += 1;
+                let stmt = prepare()?;
+                while !self.entries.is_empty() && self.entries.len() >= self.stats.capacity {
+                    self.evict();
+                }
+                self.entries.insert(query.into(), Entry { stmt, last_used: self.tick });
+            }
+        }
+        let entry = self.entries.get_mut(query).expect("statement is cached");
+        entry.last_used = self.tick;
+        Ok(&entry.stmt)
+    }
+
+    /**** set_capacity *********************************************/
+
+    /// Changes capacity, statements over the new limit are finalized.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.stats.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict();
+        }
+    }
+
+    /**** clear ****************************************************/
+
+    /// Finalizes all cached statements.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /**** stats ****************************************************/
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats { size: self.entries.len(), ..self.stats }
+    }
+
+    /// Finalizes the least recently used statement.
+    fn evict(&mut self) {
+        let lru = self.entries.iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(query, _)| query.clone());
+        if lru.and_then(|query| self.entries.remove(&query)).is_some() {
+            self.stats.evictions += 1;
+        }
+    }
+}
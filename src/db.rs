@@ -1,4 +1,3 @@
-#![allow(clippy::not_unsafe_ptr_arg_deref)]
 /*
  * Copyright (C) 2023 Piotr Pszczółkowski
  * Licence: GNU v2
@@ -10,67 +9,116 @@
  */
 extern crate sqlite3_sys;
 
-use std::collections::HashMap;
-use std::ffi::{CStr, CString};
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
 use std::ptr::null_mut;
+use std::thread;
 
-use fxhash::hash32;
 use sqlite3_sys::{sqlite3,
-                  sqlite3_stmt,
-                  sqlite3_close_v2,
+                  sqlite3_complete,
                   sqlite3_errcode,
                   sqlite3_errmsg,
                   sqlite3_exec,
+                  sqlite3_get_autocommit,
                   sqlite3_initialize,
                   sqlite3_last_insert_rowid,
                   sqlite3_libversion,
                   sqlite3_libversion_number,
-                  sqlite3_open_v2,
-                  sqlite3_shutdown,
-                  SQLITE_DONE,
+                  sqlite3_total_changes,
                   SQLITE_OK,
                   SQLITE_OPEN_CREATE,
                   SQLITE_OPEN_READONLY,
                   SQLITE_OPEN_READWRITE};
 
+use crate::blob::BlobHandle;
+use crate::cache::{CacheStats, StatementCache, DEFAULT_CAPACITY};
+use crate::error::{Error, Result};
+use crate::ffi::{from_cstr, to_cstring, DbHandle};
+use crate::migrations::Migrations;
+use crate::prepared::PreparedStatement;
+use crate::row::Row;
+use crate::rows::Rows;
 use crate::stmt::Statement;
-use crate::store::Store;
-use crate::types::*;
+use crate::store::{Params, Store};
+use crate::types::{BatchItem, Parameter, TextDecoding};
+use crate::transaction::{RetryPolicy, Savepoint, Transaction, TransactionMode};
 
 static IN_MEMORY: &str = ":memory:";
-const DB_NULL: *mut sqlite3 = null_mut();
-
-include!("macros.inc");
 
 
 /// Object handled connection with SQLite database
 /// (via c-library sqlite3).
-
 pub struct SQLite {
-    db: *mut sqlite3,
+    db: Option<DbHandle>,
     fpath: String,
-    prepared: HashMap<u32, *mut sqlite3_stmt>,
+    cache: StatementCache,
     use_prepared: bool,
+    savepoint_seq: usize,
+    retry: RetryPolicy,
+    decoding: TextDecoding,
 }
 
 impl SQLite {
     /// Inits handler object.
+    /// The sqlite3 library is initialized when the database is opened.
     pub fn new() -> SQLite {
-        println!("sqlite3: {}", SQLite::version());
-        unsafe {
-            match sqlite3_initialize() {
-                SQLITE_OK => SQLite::default(),
-                _ => panic!("can't init sqlite database engine")
-            }
-        }
+        SQLite::default()
     }
 
     /**** reause_prepared ******************************************/
 
     /// Force prepared statemt reuse.
+    /// Prepared statements are kept in LRU cache keyed by SQL text
+    /// (see 'cache_capacity').
+    /// Capacity 0 (see 'cache_capacity') is changed to the default one.
     pub fn reuse_prepared(mut self) -> Self {
         self.use_prepared = true;
+        if self.cache.stats().capacity == 0 {
+            self.cache.set_capacity(DEFAULT_CAPACITY);
+        }
+        self
+    }
+
+    /**** cache_capacity *******************************************/
+
+    /// Sets how many prepared statements are kept for reuse
+    /// (0 disables reuse). Least recently used statements
+    /// over the limit are finalized.
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.use_prepared = capacity > 0;
+        self.cache.set_capacity(capacity);
+        self
+    }
+
+    /**** cache_stats **********************************************/
+
+    /// Returns statistics of the prepared statements cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /**** clear_cache **********************************************/
+
+    /// Finalizes all prepared statements kept for reuse.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /**** retry_policy *******************************************/
+
+    /// Sets policy of repeating transactions run by 'transaction'
+    /// when the database is busy or locked.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /**** text_decoding ********************************************/
+
+    /// Sets how TEXT values which are not valid UTF-8 are read
+    /// (by default invalid sequences are replaced with U+FFFD).
+    pub fn text_decoding(mut self, decoding: TextDecoding) -> Self {
+        self.decoding = decoding;
         self
     }
 
@@ -93,217 +141,615 @@ impl SQLite {
     /**** close ****************************************************/
 
     /// Closes database.
-    pub fn close(&mut self) -> bool {
-        match self.db {
-            DB_NULL => true,
-            _ => {
-                match unsafe { sqlite3_close_v2(self.db) } {
-                    SQLITE_OK => {
-                        self.db = null_mut();
-                        true
-                    }
-                    _ => {
-                        sql_error!(self.db);
-                        false
-                    }
-                }
+    /// Prepared statements kept for reuse are finalized before.
+    pub fn close(&mut self) -> Result<()> {
+        match self.db.take() {
+            None => Ok(()),
+            Some(db) => {
+                self.cache.clear();
+                db.close()
             }
         }
     }
 
-    /**** err_string ***********************************************/
+    /**** error_string *********************************************/
 
     /// Returns last error description.
-    /// @ Safety
-    /// here row pointer is passed
-    pub fn err_string(db: *mut sqlite3) -> String {
-        unsafe {
-            let cptr = sqlite3_errmsg(db);
-            String::from_utf8_lossy(CStr::from_ptr(cptr).to_bytes()).into_owned()
-        }
-    }
     pub fn error_string(&self) -> String {
-        unsafe {
-            let cptr = sqlite3_errmsg(self.db);
-            String::from_utf8_lossy(CStr::from_ptr(cptr).to_bytes()).into_owned()
-        }
+        // sqlite3_errmsg accepts NULL (not opened database)
+        unsafe { from_cstr(sqlite3_errmsg(self.db_ptr())) }.unwrap_or_default()
     }
 
-
-    /**** err_code *************************************************/
+    /**** error_code ***********************************************/
 
     /// Returns last error code.
-    /// # Safety
-    /// here raw pointer is passed
-    pub fn err_code(db: *mut sqlite3) -> i32 {
-        unsafe { sqlite3_errcode(db) }
-    }
     pub fn error_code(&self) -> i32 {
-        unsafe { sqlite3_errcode(self.db) }
+        match self.db {
+            Some(ref db) => unsafe { sqlite3_errcode(db.as_ptr()) },
+            None => SQLITE_OK,
+        }
     }
 
 
     /**** open *****************************************************/
 
     /// Opens a database existed already on disk.
-    pub fn open(&mut self, read_only: bool) -> bool {
+    pub fn open(&mut self, read_only: bool) -> Result<()> {
         // can't open a database when is alreadey opened
-        if self.db != DB_NULL {
-            eprintln!("database already opened");
-            return false;
+        if self.db.is_some() {
+            return Err(Error::AlreadyOpened);
         }
 
         let flags = match read_only {
             true => SQLITE_OPEN_READONLY,
             false => SQLITE_OPEN_READWRITE,
         };
+        self.open_with_flags(flags)
+    }
 
-        unsafe {
-            let stat = sqlite3_open_v2(
-                str2ptr!(self.fpath.clone()),
-                &mut self.db,
-                flags,
-                std::ptr::null());
-            match stat {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
+    /**** create ***************************************************/
+
+    /// Creates and inits a new database.
+    /// Existing database file is never removed, creating it again
+    /// is Error::DatabaseExists (see 'open_or_create' and 'recreate').
+    /// Initial queries are executed in one transaction; when one
+    /// of them fails the database is closed and the new file removed.
+    pub fn create(&mut self, cmd: Vec<&str>) -> Result<()> {
+        if self.db.is_some() {
+            return Err(Error::AlreadyOpened);
+        }
+
+        // File is created atomically, so it can't be created twice.
+        if self.on_disk() {
+            if let Err(err) = OpenOptions::new().write(true).create_new(true).open(&self.fpath) {
+                return match err.kind() {
+                    ErrorKind::AlreadyExists => Err(Error::DatabaseExists(self.fpath.clone())),
+                    _ => Err(err.into()),
+                };
+            }
+        }
+
+        // Execute initial queries (create tables for example)
+        let result = self.open_with_flags(SQLITE_OPEN_CREATE | SQLITE_OPEN_READWRITE)
+            .and_then(|_| self.transaction(|tr| {
+                for query in &cmd {
+                    tr.exec(query)?;
                 }
+                Ok(())
+            }));
+        if result.is_err() {
+            let _ = self.close();
+            // the file was created above, so no data is lost
+            if self.on_disk() {
+                let _ = fs::remove_file(&self.fpath);
             }
         }
+        result
     }
 
-    /**** create ***************************************************/
+    /**** open_or_create *******************************************/
+
+    /// Opens the database, the file is created when it is missing.
+    /// Initial queries ('schema') are executed only for a new database
+    /// (without any table, index, view or trigger), in one transaction,
+    /// so the database stays empty when one of them fails.
+    /// Existing database file is never removed.
+    ///
+    /// # Returns
+    /// true when the database was new and the schema was executed
+    pub fn open_or_create(&mut self, schema: Vec<&str>) -> Result<bool> {
+        if self.db.is_some() {
+            return Err(Error::AlreadyOpened);
+        }
+        self.open_with_flags(SQLITE_OPEN_CREATE | SQLITE_OPEN_READWRITE)?;
+
+        // plain read first, so opening of a populated database
+        // doesn't wait for the write lock of other connections
+        let result = self.exists("SELECT 1 FROM sqlite_master", Store::new())
+            .and_then(|populated| match populated {
+                true => Ok(false),
+                // checked again inside the transaction, so concurrent
+                // processes can't both see the database as new
+                _ => self.transaction(|tr| {
+                    if tr.exists("SELECT 1 FROM sqlite_master", Store::new())? {
+                        return Ok(false);
+                    }
+                    for query in &schema {
+                        tr.exec(query)?;
+                    }
+                    Ok(true)
+                }),
+            });
+        if result.is_err() {
+            let _ = self.close();
+        }
+        result
+    }
 
-    /// Creates and inits a database.
-    pub fn create(&mut self, cmd: Vec<&str>) -> bool {
-        if self.db != DB_NULL {
-            eprintln!("database already opened");
-            return false;
+    /**** open_and_migrate *****************************************/
+
+    /// Opens the database (the file is created when it is missing)
+    /// and applies pending migrations (see 'Migrations').
+    /// On failure the database is closed and stays unchanged.
+    ///
+    /// # Returns
+    /// number of applied migrations
+    pub fn open_and_migrate(&mut self, migrations: &Migrations) -> Result<u32> {
+        if self.db.is_some() {
+            return Err(Error::AlreadyOpened);
+        }
+        self.open_with_flags(SQLITE_OPEN_CREATE | SQLITE_OPEN_READWRITE)?;
+        let result = self.migrate(migrations);
+        if result.is_err() {
+            let _ = self.close();
         }
+        result
+    }
+
+    /**** migrate **************************************************/
+
+    /// Applies pending migrations to the opened database
+    /// in one transaction (a savepoint inside an active transaction)
+    /// and returns number of applied migrations.
+    pub fn migrate(&mut self, migrations: &Migrations) -> Result<u32> {
+        migrations.apply(self)
+    }
+
+    /**** recreate *************************************************/
 
-        // Remove database file if on disk.
-        if self.fpath != IN_MEMORY {
+    /// Removes the database file (if exists) and creates new database.
+    /// All data of the existing database is lost.
+    pub fn recreate(&mut self, cmd: Vec<&str>) -> Result<()> {
+        if self.db.is_some() {
+            return Err(Error::AlreadyOpened);
+        }
+        if self.on_disk() {
             if let Err(err) = fs::remove_file(&self.fpath) {
                 match err.kind() {
-                    std::io::ErrorKind::NotFound => (),
-                    _ => {
-                        eprintln!("{}", err);
-                        return false;
-                    }
+                    ErrorKind::NotFound => (),
+                    _ => return Err(err.into()),
                 }
             };
         }
-        unsafe {
-            let stat = sqlite3_open_v2(
-                str2ptr!(self.fpath.clone()),
-                &mut self.db,
-                SQLITE_OPEN_CREATE | SQLITE_OPEN_READWRITE,
-                std::ptr::null());
-            match stat {
-                SQLITE_OK => {
-                    // Execute initial queries (create tables for example)
-                    for query in cmd {
-                        if !self.exec(query) {
-                            return false;
-                        }
-                    }
-                    true
-                }
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+        self.create(cmd)
+    }
+
+    /// Checks whether the database is stored in a file with plain path
+    /// (not in memory, not temporary, not URI).
+    fn on_disk(&self) -> bool {
+        !(self.fpath.is_empty() || self.fpath == IN_MEMORY || self.fpath.starts_with("file:"))
+    }
+
+    /**** open_with_flags ******************************************/
+
+    /// Opens the connection with passed sqlite3 flags.
+    /// On failure the handle is released, so the object may be reused.
+    fn open_with_flags(&mut self, flags: i32) -> Result<()> {
+        match unsafe { sqlite3_initialize() } {
+            SQLITE_OK => (),
+            rc => return Err(Error::from_code(rc)),
         }
+        self.db = Some(DbHandle::open(&self.fpath, flags)?);
+        Ok(())
+    }
+
+    /**** handle ***************************************************/
+
+    /// Returns handle of the connection for sqlite3 calls
+    /// or error when the database is not opened.
+    fn handle(&self) -> Result<&DbHandle> {
+        self.db.as_ref().ok_or(Error::NotOpened)
+    }
+
+    /// Returns pointer to the connection or NULL when not opened.
+    fn db_ptr(&self) -> *mut sqlite3 {
+        self.db.as_ref().map_or(null_mut(), DbHandle::as_ptr)
     }
 
     /**** exec *****************************************************/
 
     /// Executes a query without parameters
-    pub fn exec(&mut self, query: &str) -> bool {
-        if self.db == DB_NULL {
-            eprintln!("database is not opened");
-            return false;
-        }
-        unsafe {
-            let stat = sqlite3_exec(
-                self.db,
-                str2ptr!(query),
+    pub fn exec(&mut self, query: &str) -> Result<()> {
+        let db = self.handle()?.as_ptr();
+        let sql = to_cstring(query)?;
+        let stat = unsafe {
+            sqlite3_exec(
+                db,
+                sql.as_ptr(),
                 None,
                 std::ptr::null_mut(),
-                std::ptr::null_mut());
-            match stat {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+                std::ptr::null_mut())
+        };
+        match stat {
+            SQLITE_OK => Ok(()),
+            _ => Err(Error::from_db(db).with_sql(query))
         }
     }
 
     /**** exec_query ***********************************************/
 
     /// Executes a query with passed arguments.
-    pub fn exec_query(&mut self, query: &str, args: Store) -> bool {
-        if self.db == DB_NULL {
-            eprintln!("database is not opened");
-            return false;
-        }
-
-        if let Some(mut stmt) = self.stmt_for_query(query) {
-            if stmt.bind(args) && SQLITE_DONE == stmt.step() {
-                return true;
-            }
-        }
-        sql_error!(self.db);
-        false
+    /// Returns number of rows changed by INSERT, UPDATE or DELETE.
+    /// Rows returned by the query (e.g. RETURNING clause) are skipped.
+    pub fn exec_query<P: Params>(&mut self, query: &str, args: P) -> Result<u64> {
+        let mut stmt = self.stmt_for_query(query)?;
+        stmt.bind(&args).map_err(|err| err.with_sql(query))?;
+        stmt.execute().map_err(|err| err.with_sql(query))
     }
 
     /**** insert ***************************************************/
 
     /// Executes INSERT command with arguments
     /// and returns 'rowid' of inserted row.
-    pub fn insert(&mut self, query: &str, args: Store) -> i64 {
-        match self.exec_query(query, args) {
-            true => self.last_inserted_id(),
-            _ => 0
-        }
+    pub fn insert<P: Params>(&mut self, query: &str, args: P) -> Result<i64> {
+        self.exec_query(query, args)?;
+        Ok(self.last_inserted_id())
+    }
+
+    /**** insert_returning *****************************************/
+
+    /// Executes INSERT command with RETURNING clause
+    /// and returns rows produced by the clause
+    /// (e.g. generated keys, defaults or timestamps).
+    ///
+    /// ```ignore
+    /// let row = db.insert_returning("INSERT INTO person (name) VALUES (?) RETURNING id, created", params!["Luke"])?;
+    /// ```
+    pub fn insert_returning<P: Params>(&mut self, query: &str, args: P) -> Result<Vec<Row>> {
+        self.select(query, args)
+    }
+
+    /**** update_returning *****************************************/
+
+    /// Executes UPDATE command with RETURNING clause
+    /// and returns rows produced by the clause (one per updated row).
+    pub fn update_returning<P: Params>(&mut self, query: &str, args: P) -> Result<Vec<Row>> {
+        self.select(query, args)
+    }
+
+    /**** delete_returning *****************************************/
+
+    /// Executes DELETE command with RETURNING clause
+    /// and returns rows produced by the clause (one per deleted row).
+    pub fn delete_returning<P: Params>(&mut self, query: &str, args: P) -> Result<Vec<Row>> {
+        self.select(query, args)
     }
 
     /**** select ***************************************************/
 
     /// Executes SELECT command with argumets
-    /// and returns fetched rows.
-    pub fn select(&mut self, query: &str, args: Store) -> Option<Vec<Row>> {
-        if DB_NULL == self.db {
-            eprintln!("database is not opened");
-            return None;
+    /// and returns fetched rows (empty vector when nothing matched).
+    pub fn select<P: Params>(&mut self, query: &str, args: P) -> Result<Vec<Row>> {
+        let mut stmt = self.stmt_for_query(query)?;
+        stmt.bind(&args).map_err(|err| err.with_sql(query))?;
+        stmt.fetch_result().map_err(|err| err.with_sql(query))
+    }
+
+    /**** select_one ***********************************************/
+
+    /// Executes SELECT command which must return exactly one row.
+    /// Returns error QueryReturnedNoRows or QueryReturnedMoreThanOneRow otherwise.
+    pub fn select_one<P: Params>(&mut self, query: &str, args: P) -> Result<Row> {
+        self.select_optional(query, args)?
+            .ok_or(Error::QueryReturnedNoRows)
+    }
+
+    /**** select_optional ******************************************/
+
+    /// Executes SELECT command which may return at most one row.
+    /// Returns error QueryReturnedMoreThanOneRow when more rows were found.
+    pub fn select_optional<P: Params>(&mut self, query: &str, args: P) -> Result<Option<Row>> {
+        let mut rows = self.query(query, args)?;
+        let row = match rows.next() {
+            Some(row) => row?,
+            None => return Ok(None),
+        };
+        match rows.next() {
+            Some(Err(err)) => Err(err),
+            Some(_) => Err(Error::QueryReturnedMoreThanOneRow),
+            None => Ok(Some(row)),
         }
+    }
 
-        if let Some(mut stmt) = self.stmt_for_query(query) {
-            if stmt.bind(args) {
-                return stmt.fetch_result();
-            }
+    /**** exists ***************************************************/
+
+    /// Executes SELECT command and checks whether it returned any row.
+    pub fn exists<P: Params>(&mut self, query: &str, args: P) -> Result<bool> {
+        match self.query(query, args)?.next() {
+            Some(row) => row.map(|_| true),
+            None => Ok(false),
         }
-        sql_error!(self.db);
-        None
+    }
+
+    /**** query ****************************************************/
+
+    /// Executes SELECT command with arguments and returns
+    /// a lazy cursor over its rows (rows are fetched on demand).
+    pub fn query<'a, P>(&'a mut self, query: &str, args: P) -> Result<Rows<'a>>
+        where P: Params + 'a
+    {
+        let mut stmt = self.stmt_for_query(query)?;
+        stmt.bind(&args).map_err(|err| err.with_sql(query))?;
+        Ok(Rows::new(stmt))
+    }
+
+    /**** prepare **************************************************/
+
+    /// Prepares a statement for repeated execution.
+    /// The statement bypasses the cache and is finalized when dropped.
+    pub fn prepare(&self, query: &str) -> Result<PreparedStatement<'_>> {
+        let stmt = Statement::for_query(self.handle()?, query)?;
+        Ok(PreparedStatement::new(stmt.decoding(self.decoding)))
+    }
+
+    /**** open_blob ************************************************/
+
+    /// Opens blob of the column in the row (by rowid) of the table
+    /// in the main database for incremental I/O.
+    ///
+    /// ```ignore
+    /// let id = db.insert("INSERT INTO file (data) VALUES (?)", StoreRef::new().add_zeroblob(size))?;
+    /// io::copy(&mut fs::File::open(path)?, &mut db.open_blob("file", "data", id, false)?)?;
+    /// ```
+    pub fn open_blob(&self, table: &str, column: &str, rowid: i64, read_only: bool) -> Result<BlobHandle<'_>> {
+        self.open_blob_in("main", table, column, rowid, read_only)
+    }
+
+    /**** open_blob_in *********************************************/
+
+    /// Opens blob in the passed database ('main', 'temp' or name of
+    /// attached database), see 'open_blob'.
+    pub fn open_blob_in(&self, database: &str, table: &str, column: &str, rowid: i64, read_only: bool) -> Result<BlobHandle<'_>> {
+        BlobHandle::open(self.handle()?.as_ptr(), database, table, column, rowid, read_only)
+    }
+
+    /**** parameters *********************************************/
+
+    /// Returns parameters (placeholders) of passed query.
+    pub fn parameters(&mut self, query: &str) -> Result<Vec<Parameter>> {
+        Ok(self.stmt_for_query(query)?.parameters())
     }
 
     /**** update ***************************************************/
 
-    /// Executes UPDATE command with arguments.
-    pub fn update(&mut self, query: &str, args: Store) -> bool {
+    /// Executes UPDATE command with arguments
+    /// and returns number of updated rows.
+    pub fn update<P: Params>(&mut self, query: &str, args: P) -> Result<u64> {
+        self.exec_query(query, args)
+    }
+
+    /**** delete ***************************************************/
+
+    /// Executes DELETE command with arguments
+    /// and returns number of deleted rows.
+    pub fn delete<P: Params>(&mut self, query: &str, args: P) -> Result<u64> {
         self.exec_query(query, args)
     }
 
+    /**** execute_batch ********************************************/
+
+    /// Executes one query for every passed set of arguments
+    /// inside a single (IMMEDIATE) transaction.
+    /// The query is prepared once, the statement is reset and bound again
+    /// for every item. Returns rowid and number of changed rows of every item.
+    /// On the first failure the transaction is rolled back and Error::Batch
+    /// with index of the failing item is returned.
+    ///
+    /// ```ignore
+    /// let people = vec![params!["Luke", 19], params!["Leia", 19]];
+    /// let items = db.execute_batch("INSERT INTO person (name, age) VALUES (?, ?)", people)?;
+    /// ```
+    pub fn execute_batch<I, P>(&mut self, query: &str, items: I) -> Result<Vec<BatchItem>>
+        where I: IntoIterator<Item = P>,
+              P: Params
+    {
+        self.execute_batch_with(query, items, Some(TransactionMode::Immediate))
+    }
+
+    /**** execute_batch_with ***************************************/
+
+    /// Executes batch (see 'execute_batch') in a transaction with passed mode.
+    /// When a transaction is already active (e.g. the batch is executed
+    /// through Transaction or Savepoint) a savepoint is used instead.
+    /// With None no transaction is started; items executed before
+    /// a failure stay then.
+    pub fn execute_batch_with<I, P>(&mut self, query: &str, items: I, mode: Option<TransactionMode>) -> Result<Vec<BatchItem>>
+        where I: IntoIterator<Item = P>,
+              P: Params
+    {
+        match mode {
+            None => self.run_batch(query, items),
+            Some(mode) => self.atomically(mode, |db| db.run_batch(query, items)),
+        }
+    }
+
+    /// Executes items of a batch with one statement.
+    fn run_batch<I, P>(&mut self, query: &str, items: I) -> Result<Vec<BatchItem>>
+        where I: IntoIterator<Item = P>,
+              P: Params
+    {
+        let mut stmt = Statement::for_query(self.handle()?, query)?;
+        let mut result = Vec::new();
+
+        for (index, args) in items.into_iter().enumerate() {
+            let changes = stmt.bind(&args).and_then(|_| stmt.execute());
+            stmt.reset();
+            stmt.clear_bindings();
+            match changes {
+                Ok(changes) => result.push(BatchItem { rowid: self.last_inserted_id(), changes }),
+                Err(err) => return Err(Error::Batch { index, error: Box::new(err.with_sql(query)) }),
+            }
+        }
+        Ok(result)
+    }
+
+    /**** execute_script *******************************************/
+
+    /// Executes script of many statements separated with ';'.
+    /// Statements are prepared and executed one by one (rows returned
+    /// by them are skipped). On failure Error::Script tells which
+    /// statement failed (number, byte offset and line in the script),
+    /// statements executed before stay. Returns number of executed statements.
+    pub fn execute_script(&mut self, script: &str) -> Result<usize> {
+        self.execute_script_with(script, None)
+    }
+
+    /**** execute_script_with **************************************/
+
+    /// Executes script (see 'execute_script') inside a transaction
+    /// with passed mode, so on failure none of its statements stays.
+    /// When a transaction is already active a savepoint is used instead.
+    /// The script can't contain own BEGIN/COMMIT then.
+    pub fn execute_script_with(&mut self, script: &str, mode: Option<TransactionMode>) -> Result<usize> {
+        match mode {
+            None => self.run_script(script),
+            Some(mode) => self.atomically(mode, |db| db.run_script(script)),
+        }
+    }
+
+    /// Executes statements of a script walking it with prepare tail.
+    fn run_script(&mut self, script: &str) -> Result<usize> {
+        let db = self.handle()?;
+        let sql = to_cstring(script)?;
+        let mut offset = 0;
+        let mut ordinal = 0;
+
+        while offset < script.len() {
+            let start = statement_start(script, offset);
+            let (stmt, next) = Statement::for_script(db, &sql, offset)
+                .map_err(|err| script_error(script, ordinal + 1, start, statement_end(script, start), err))?;
+            match stmt {
+                Some(stmt) => {
+                    ordinal += 1;
+                    stmt.execute().map_err(|err| script_error(script, ordinal, start, next, err))?;
+                }
+                None if next > offset => (),
+                None => break,
+            }
+            offset = next;
+        }
+        Ok(ordinal)
+    }
+
+    /**** total_changes ********************************************/
+
+    /// Returns number of rows changed by INSERT, UPDATE and DELETE
+    /// (also by triggers) since the connection was opened.
+    pub fn total_changes(&self) -> u64 {
+        match &self.db {
+            Some(db) => unsafe { sqlite3_total_changes(db.as_ptr()) as u64 },
+            None => 0,
+        }
+    }
+
+    /**** begin **************************************************/
+
+    /// Begins a DEFERRED transaction.
+    /// Returned guard rolls back uncommitted changes when dropped.
+    pub fn begin(&mut self) -> Result<Transaction<'_>> {
+        self.begin_with(TransactionMode::Deferred)
+    }
+
+    /**** begin_with ***********************************************/
+
+    /// Begins a transaction in passed mode
+    /// (DEFERRED, IMMEDIATE or EXCLUSIVE).
+    pub fn begin_with(&mut self, mode: TransactionMode) -> Result<Transaction<'_>> {
+        Transaction::new(self, mode)
+    }
+
+    /**** transaction ********************************************/
+
+    /// Runs passed closure inside an IMMEDIATE transaction.
+    /// The transaction is committed when the closure returns Ok
+    /// and rolled back when it returns Err.
+    ///
+    /// When begin or commit fails with SQLITE_BUSY/SQLITE_LOCKED
+    /// the whole transaction (with the closure) is repeated
+    /// according to the retry policy (see 'retry_policy').
+    pub fn transaction<T, F>(&mut self, f: F) -> Result<T>
+        where F: FnMut(&mut Transaction) -> Result<T>
+    {
+        self.transaction_with(TransactionMode::Immediate, f)
+    }
+
+    /**** transaction_with *****************************************/
+
+    /// Runs passed closure inside a transaction begun in passed mode.
+    /// See 'transaction'.
+    pub fn transaction_with<T, F>(&mut self, mode: TransactionMode, mut f: F) -> Result<T>
+        where F: FnMut(&mut Transaction) -> Result<T>
+    {
+        let mut attempt = 0;
+        loop {
+            let err = match Transaction::new(self, mode) {
+                Ok(mut tx) => {
+                    // On error 'tx' is dropped, so changes are rolled back.
+                    let value = f(&mut tx)?;
+                    match tx.commit() {
+                        Ok(()) => return Ok(value),
+                        Err(err) => err,
+                    }
+                }
+                Err(err) => err,
+            };
+            if !err.is_busy() || attempt >= self.retry.max_retries {
+                return Err(err);
+            }
+            thread::sleep(self.retry.delay(attempt));
+            attempt += 1;
+        }
+    }
+
+    /**** savepoint **********************************************/
+
+    /// Starts a savepoint with generated unique name.
+    /// Returned guard rolls back to the savepoint when dropped.
+    pub fn savepoint(&mut self) -> Result<Savepoint<'_>> {
+        self.savepoint_seq += 1;
+        let name = format!("sp_{}", self.savepoint_seq);
+        Savepoint::new(self, name)
+    }
+
+    /**** atomically *********************************************/
+
+    /// Runs 'f' in a transaction begun in passed mode or, when
+    /// a transaction is already active, in a savepoint. Changes
+    /// made by 'f' are rolled back when it fails.
+    fn atomically<T, F>(&mut self, mode: TransactionMode, f: F) -> Result<T>
+        where F: FnOnce(&mut SQLite) -> Result<T>
+    {
+        match self.in_transaction() {
+            true => {
+                let mut sp = self.savepoint()?;
+                let value = f(&mut sp)?;
+                sp.release()?;
+                Ok(value)
+            }
+            _ => {
+                let mut tr = self.begin_with(mode)?;
+                let value = f(&mut tr)?;
+                tr.commit()?;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Returns true when a transaction is active
+    /// (the connection isn't in autocommit mode).
+    pub(crate) fn in_transaction(&self) -> bool {
+        self.db.as_ref()
+            .is_some_and(|db| unsafe { sqlite3_get_autocommit(db.as_ptr()) } == 0)
+    }
+
     /**** last_inserted_id *****************************************/
 
     /// Returns last inserted 'rowid'
     fn last_inserted_id(&self) -> i64 {
-        unsafe { sqlite3_last_insert_rowid(self.db) }
+        match self.db {
+            Some(ref db) => unsafe { sqlite3_last_insert_rowid(db.as_ptr()) },
+            None => 0,
+        }
     }
 
     /**** version_number *******************************************/
@@ -320,36 +766,22 @@ impl SQLite {
     /// Returns library version as text.
     pub fn version() -> String {
         unsafe {
-            CStr::from_ptr(sqlite3_libversion()).to_string_lossy().into_owned()
+            from_cstr(sqlite3_libversion()).unwrap_or_default()
         }
     }
 
     /**** stmt_for_query *******************************************/
 
     /// Creates or looking for statement.
-    fn stmt_for_query(&mut self, query: &str) -> Option<Statement> {
-        match self.use_prepared {
-            true => {
-                let query_hash = hash32(query);
-                match self.prepared.get(&query_hash) {
-                    Some(stmt) => {
-                        println!("found previously prepared statement [{} - {}]", query_hash, query);
-                        Some(Statement::for_stmt(self.db, *stmt))
-                    },
-                    _ => {
-                        eprintln!("new statement preparation [{}]", query);
-                        if let Some(statement) = Statement::for_query(self.db, query) {
-                            println!("hash of prepared statemnt {}", query_hash);
-                            self.prepared.insert(query_hash, statement.stmt);
-                            return Some(statement);
-                        }
-                        None
-                    }
-                }
-            }
-            // every time statements should be prepared
-            _ => Statement::for_query(self.db, query),
+    fn stmt_for_query(&mut self, query: &str) -> Result<Statement<'_>> {
+        let db = self.db.as_ref().ok_or(Error::NotOpened)?;
+        // every time statements should be prepared
+        if !self.use_prepared {
+            return Ok(Statement::for_query(db, query)?.decoding(self.decoding));
         }
+        // cache becomes owner of the new statement
+        let stmt = self.cache.get_or_prepare(query, || Statement::prepare(db, query))?;
+        Ok(Statement::for_stmt(db, stmt).decoding(self.decoding))
     }
 }
 
@@ -362,10 +794,13 @@ impl SQLite {
 impl Default for SQLite {
     fn default() -> Self {
         SQLite {
-            db: null_mut(),
+            db: None,
             fpath: "".into(),
-            prepared: HashMap::new(),
+            cache: StatementCache::with_capacity(DEFAULT_CAPACITY),
             use_prepared: false,
+            savepoint_seq: 0,
+            retry: RetryPolicy::default(),
+            decoding: TextDecoding::default(),
         }
     }
 }
@@ -378,11 +813,52 @@ impl Default for SQLite {
 
 impl Drop for SQLite {
     fn drop(&mut self) {
-        unsafe {
-            if self.close() && SQLITE_OK == sqlite3_shutdown() {
-                return;
-            }
-            sql_error!(self.db);
+        // Errors can't be reported from drop.
+        // sqlite3_shutdown is not called, other connections may be still opened.
+        let _ = self.close();
+    }
+}
+
+/// Creates error of the statement of a script placed at 'start..end'.
+fn script_error(script: &str, ordinal: usize, start: usize, end: usize, error: Error) -> Error {
+    Error::Script {
+        ordinal,
+        offset: start,
+        line: script[..start].matches('\n').count() + 1,
+        error: Box::new(error.with_sql(script[start..end].trim_end())),
+    }
+}
+
+/// Returns offset of the first character of a statement starting
+/// at 'offset' (after white spaces and comments).
+fn statement_start(script: &str, mut offset: usize) -> usize {
+    loop {
+        let rest = &script[offset..];
+        let trimmed = rest.trim_start();
+        let skipped = if trimmed.starts_with("--") {
+            trimmed.find('\n').map_or(trimmed.len(), |pos| pos + 1)
+        } else if trimmed.starts_with("/*") {
+            trimmed.find("*/").map_or(trimmed.len(), |pos| pos + 2)
+        } else {
+            0
+        };
+        let next = offset + (rest.len() - trimmed.len()) + skipped;
+        if next == offset {
+            return offset;
         }
+        offset = next;
     }
 }
+
+/// Returns offset following the statement starting at 'start'
+/// (after its terminating ';' or end of the script).
+/// Used when the statement can't be prepared, so its tail is unknown.
+fn statement_end(script: &str, start: usize) -> usize {
+    script[start..].match_indices(';')
+        .map(|(pos, _)| start + pos + 1)
+        .find(|&end| match to_cstring(&script[start..end]) {
+            Ok(sql) => unsafe { sqlite3_complete(sql.as_ptr()) != 0 },
+            _ => false,
+        })
+        .unwrap_or(script.len())
+}
@@ -0,0 +1,272 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: error.rs
+ */
+extern crate sqlite3_sys;
+
+use std::ffi::{CStr, NulError};
+use std::str::Utf8Error;
+use std::fmt;
+
+use libc::c_int;
+use sqlite3_sys::{sqlite3,
+                  sqlite3_errmsg,
+                  sqlite3_errstr,
+                  sqlite3_extended_errcode,
+                  SQLITE_BUSY,
+                  SQLITE_LOCKED};
+
+use crate::value::FromValueError;
+
+/// Result type returned by all crate operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors reported by the crate.
+#[derive(Debug)]
+pub enum Error {
+    /// Error reported by the sqlite3 library.
+    Sqlite {
+        /// Primary result code (e.g. SQLITE_BUSY).
+        code: i32,
+        /// Extended result code (e.g. SQLITE_BUSY_SNAPSHOT).
+        extended_code: i32,
+        /// Text returned by sqlite3_errmsg.
+        message: String,
+        /// SQL of the query which failed (if any).
+        sql: Option<String>,
+        /// 1-based index of the parameter which failed to bind (if any).
+        param: Option<usize>,
+    },
+    /// Operation requires an opened database.
+    NotOpened,
+    /// Database is opened already.
+    AlreadyOpened,
+    /// Database file to be created exists already.
+    DatabaseExists(String),
+    /// Query expected to return a row returned none.
+    QueryReturnedNoRows,
+    /// Query expected to return at most one row returned more.
+    QueryReturnedMoreThanOneRow,
+    /// Row has no column with passed index.
+    InvalidColumnIndex(usize),
+    /// Row has no column with passed name.
+    InvalidColumnName(String),
+    /// Query has no parameter with passed name.
+    UnknownParameter(String),
+    /// No value was passed for the named parameter of the query.
+    UnboundParameter(String),
+    /// More than one value was passed for the same parameter of the query.
+    DuplicateParameter(String),
+    /// Number of passed values doesn't match parameters of the query.
+    ParameterCount {
+        /// Number of parameters of the query.
+        expected: usize,
+        /// Number of passed values.
+        supplied: usize,
+        /// Names of query parameters ('?' for anonymous).
+        names: Vec<String>,
+    },
+    /// Value of the column can't be converted to requested type.
+    FromValue { column: String, error: FromValueError },
+    /// TEXT value of the column is not valid UTF-8 (TextDecoding::Strict).
+    InvalidUtf8 { column: String, error: Utf8Error },
+    /// Item of a batch failed (see SQLite::execute_batch).
+    Batch {
+        /// 0-based index of the failing item.
+        index: usize,
+        /// Error of the item.
+        error: Box<Error>,
+    },
+    /// Statement of a script failed (see SQLite::execute_script).
+    Script {
+        /// 1-based number of the failing statement.
+        ordinal: usize,
+        /// Byte offset of the statement in the script.
+        offset: usize,
+        /// 1-based line of the statement in the script.
+        line: usize,
+        /// Error of the statement.
+        error: Box<Error>,
+    },
+    /// Migration of the database schema failed (see Migrations).
+    Migration {
+        /// Version (1-based number) of the failing migration.
+        version: u32,
+        /// Error of the migration.
+        error: Box<Error>,
+    },
+    /// Database schema has version newer than known migrations.
+    DatabaseTooNew {
+        /// Version of the database schema.
+        version: u32,
+        /// Latest version known to the code.
+        latest: u32,
+    },
+    /// Text passed to sqlite3 (SQL, path, name) contains NUL byte.
+    Nul(NulError),
+    /// I/O error outside the sqlite3 library.
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// Creates error from the last error state of the connection.
+    pub(crate) fn from_db(db: *mut sqlite3) -> Error {
+        let (extended_code, message) = unsafe {
+            let cptr = sqlite3_errmsg(db);
+            (sqlite3_extended_errcode(db), CStr::from_ptr(cptr).to_string_lossy().into_owned())
+        };
+        Error::Sqlite {
+            code: extended_code & 0xff,
+            extended_code,
+            message,
+            sql: None,
+            param: None,
+        }
+    }
+
+    /// Creates error for result code only (used when the connection
+    /// is not available).
+    pub(crate) fn from_code(rc: c_int) -> Error {
+        let message = unsafe {
+            CStr::from_ptr(sqlite3_errstr(rc)).to_string_lossy().into_owned()
+        };
+        Error::Sqlite {
+            code: rc & 0xff,
+            extended_code: rc,
+            message,
+            sql: None,
+            param: None,
+        }
+    }
+
+    /// Attaches SQL of the failing query.
+    pub(crate) fn with_sql(mut self, query: &str) -> Error {
+        if let Error::Sqlite { ref mut sql, .. } = self {
+            *sql = Some(query.into());
+        }
+        self
+    }
+
+    /// Attaches index of the failing parameter.
+    pub(crate) fn with_param(mut self, idx: usize) -> Error {
+        if let Error::Sqlite { ref mut param, .. } = self {
+            *param = Some(idx);
+        }
+        self
+    }
+
+    /**** code *****************************************************/
+
+    /// Returns primary sqlite3 result code (if error comes from sqlite3).
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Error::Sqlite { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /**** extended_code ********************************************/
+
+    /// Returns extended sqlite3 result code (if error comes from sqlite3).
+    pub fn extended_code(&self) -> Option<i32> {
+        match self {
+            Error::Sqlite { extended_code, .. } => Some(*extended_code),
+            _ => None,
+        }
+    }
+
+    /**** is_busy **************************************************/
+
+    /// Returns true when the database was busy or locked
+    /// (SQLITE_BUSY, SQLITE_LOCKED), so the operation may be repeated.
+    pub fn is_busy(&self) -> bool {
+        matches!(self.code(), Some(SQLITE_BUSY) | Some(SQLITE_LOCKED))
+    }
+
+    /**** sql ******************************************************/
+
+    /// Returns SQL of the failing query (if known).
+    pub fn sql(&self) -> Option<&str> {
+        match self {
+            Error::Sqlite { sql, .. } => sql.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                    D i s p l a y   T r a i t                      *
+*                                                                   *
+********************************************************************/
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Sqlite { code, extended_code, message, sql, param } => {
+                write!(f, "{} (code: {}, extended: {})", message, code, extended_code)?;
+                if let Some(idx) = param {
+                    write!(f, " at parameter {}", idx)?;
+                }
+                if let Some(sql) = sql {
+                    write!(f, " in query: {}", sql)?;
+                }
+                Ok(())
+            }
+            Error::NotOpened => write!(f, "database is not opened"),
+            Error::AlreadyOpened => write!(f, "database already opened"),
+            Error::DatabaseExists(path) => write!(f, "database already exists: {}", path),
+            Error::QueryReturnedNoRows => write!(f, "query returned no rows"),
+            Error::QueryReturnedMoreThanOneRow => write!(f, "query returned more than one row"),
+            Error::InvalidColumnIndex(idx) => write!(f, "invalid column index: {}", idx),
+            Error::InvalidColumnName(name) => write!(f, "invalid column name: {}", name),
+            Error::UnknownParameter(name) => write!(f, "unknown parameter: {}", name),
+            Error::UnboundParameter(name) => write!(f, "no value for parameter: {}", name),
+            Error::DuplicateParameter(name) => write!(f, "more than one value for parameter: {}", name),
+            Error::ParameterCount { expected, supplied, names } =>
+                write!(f, "query expects {} parameters ({}), {} supplied", expected, names.join(", "), supplied),
+            Error::FromValue { column, error } => write!(f, "column '{}': {}", column, error),
+            Error::InvalidUtf8 { column, error } => write!(f, "column '{}': invalid UTF-8 text: {}", column, error),
+            Error::Batch { index, error } => write!(f, "batch item {}: {}", index, error),
+            Error::Script { ordinal, offset, line, error } =>
+                write!(f, "script statement {} (line {}, offset {}): {}", ordinal, line, offset, error),
+            Error::Migration { version, error } => write!(f, "migration to version {}: {}", version, error),
+            Error::DatabaseTooNew { version, latest } =>
+                write!(f, "database schema version {} is newer than latest known version {}", version, latest),
+            Error::Nul(err) => write!(f, "text contains NUL byte at position {}", err.nul_position()),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Nul(err) => Some(err),
+            Error::Batch { error, .. } => Some(error.as_ref()),
+            Error::Script { error, .. } => Some(error.as_ref()),
+            Error::Migration { error, .. } => Some(error.as_ref()),
+            Error::InvalidUtf8 { error, .. } => Some(error),
+            Error::FromValue { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(err: NulError) -> Self {
+        Error::Nul(err)
+    }
+}
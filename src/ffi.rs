@@ -0,0 +1,163 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: ffi.rs
+ */
+extern crate sqlite3_sys;
+
+use std::ffi::{c_void, CStr, CString};
+use std::mem::{forget, transmute};
+use std::ptr::{null, null_mut, NonNull};
+
+use libc::{c_char, c_int};
+use sqlite3_sys::{sqlite3,
+                  sqlite3_close_v2,
+                  sqlite3_finalize,
+                  sqlite3_open_v2,
+                  sqlite3_prepare_v2,
+                  sqlite3_stmt,
+                  SQLITE_OK};
+
+use crate::error::{Error, Result};
+
+/// Owned handle of an opened sqlite3 connection.
+/// The connection is closed when the handle is dropped.
+pub(crate) struct DbHandle(NonNull<sqlite3>);
+
+impl DbHandle {
+    /**** open *****************************************************/
+
+    /// Opens connection to the database file with passed sqlite3 flags.
+    pub(crate) fn open(fpath: &str, flags: c_int) -> Result<DbHandle> {
+        let fpath = to_cstring(fpath)?;
+        let mut db = null_mut();
+        let stat = unsafe { sqlite3_open_v2(fpath.as_ptr(), &mut db, flags, null()) };
+
+        match (stat, NonNull::new(db)) {
+            (SQLITE_OK, Some(db)) => Ok(DbHandle(db)),
+            // sqlite3 returns handle also on failure, it must be released
+            (_, Some(db)) => {
+                let err = Error::from_db(db.as_ptr());
+                unsafe { sqlite3_close_v2(db.as_ptr()) };
+                Err(err)
+            }
+            (_, None) => Err(Error::from_code(stat)),
+        }
+    }
+
+    /**** as_ptr ***************************************************/
+
+    /// Returns raw pointer for sqlite3 calls.
+    /// The pointer is valid as long as the handle lives.
+    pub(crate) fn as_ptr(&self) -> *mut sqlite3 {
+        self.0.as_ptr()
+    }
+
+    /**** close ****************************************************/
+
+    /// Closes the connection.
+    /// All statements of the connection should be finalized before.
+    pub(crate) fn close(self) -> Result<()> {
+        let db = self.as_ptr();
+        forget(self);
+        match unsafe { sqlite3_close_v2(db) } {
+            SQLITE_OK => Ok(()),
+            _ => Err(Error::from_db(db)),
+        }
+    }
+}
+
+impl Drop for DbHandle {
+    fn drop(&mut self) {
+        unsafe { sqlite3_close_v2(self.as_ptr()) };
+    }
+}
+
+/// Owned handle of a prepared sqlite3 statement.
+/// The statement is finalized when the handle is dropped.
+pub(crate) struct StmtHandle(NonNull<sqlite3_stmt>);
+
+impl StmtHandle {
+    /**** prepare **************************************************/
+
+    /// Prepares the first statement of 'sql' starting at byte 'offset'.
+    ///
+    /// # Returns
+    /// handle (None when the rest is empty or contains only comments)
+    /// and byte offset of the text following the statement
+    pub(crate) fn prepare(db: &DbHandle, sql: &CStr, offset: usize) -> Result<(Option<StmtHandle>, usize)> {
+        let len = sql.to_bytes().len();
+        let mut stmt = null_mut();
+        let mut tail = null();
+        let stat = unsafe {
+            let start = sql.as_ptr().add(offset);
+            sqlite3_prepare_v2(db.as_ptr(), start, (len - offset) as c_int, &mut stmt, &mut tail)
+        };
+        if stat != SQLITE_OK {
+            return Err(Error::from_db(db.as_ptr()));
+        }
+        let next = match tail.is_null() {
+            true => len,
+            _ => tail as usize - sql.as_ptr() as usize,
+        };
+        Ok((NonNull::new(stmt).map(StmtHandle), next))
+    }
+
+    /**** as_ptr ***************************************************/
+
+    /// Returns raw pointer for sqlite3 calls.
+    /// The pointer is valid as long as the handle lives.
+    pub(crate) fn as_ptr(&self) -> *mut sqlite3_stmt {
+        self.0.as_ptr()
+    }
+}
+
+impl Drop for StmtHandle {
+    fn drop(&mut self) {
+        unsafe { sqlite3_finalize(self.as_ptr()) };
+    }
+}
+
+/**** to_cstring ***************************************************/
+
+/// Converts text to C string owned by the caller (freed when dropped).
+/// Text with interior NUL byte is an error.
+pub(crate) fn to_cstring(text: &str) -> Result<CString> {
+    Ok(CString::new(text)?)
+}
+
+/**** from_cstr ****************************************************/
+
+/// Copies C string returned by sqlite3 (None for NULL pointer).
+///
+/// # Safety
+/// pointer must be NULL or point to NUL-terminated string
+pub(crate) unsafe fn from_cstr(ptr: *const c_char) -> Option<String> {
+    match ptr.is_null() {
+        true => None,
+        _ => Some(String::from_utf8_lossy(CStr::from_ptr(ptr).to_bytes()).into_owned()),
+    }
+}
+
+/// Destructor of data bound to a statement (or special flag).
+pub(crate) type Destructor = Option<extern "C" fn(*mut c_void)>;
+
+/**** sqlite_transient *********************************************/
+
+/// Destructor flag telling sqlite3 to make its own copy of bound data.
+pub(crate) fn sqlite_transient() -> Destructor {
+    unsafe { transmute::<*const c_void, Destructor>(!0 as *const c_void) }
+}
+
+/**** sqlite_static ************************************************/
+
+/// Destructor flag telling sqlite3 to use bound data without copying.
+/// The data must not change or be freed until the statement is reset
+/// (or the parameter is bound again).
+pub(crate) fn sqlite_static() -> Destructor {
+    None
+}
@@ -7,8 +7,17 @@
  * Project: rs-sqlite
  * File: lib.rs
  */
+pub mod blob;
+pub mod cache;
 pub mod db;
+pub mod error;
+pub(crate) mod ffi;
+pub mod migrations;
+pub mod prepared;
+pub mod row;
+pub mod rows;
 pub mod store;
+pub mod transaction;
 pub mod types;
 pub mod value;
 pub(crate) mod stmt;
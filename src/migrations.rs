@@ -0,0 +1,150 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: migrations.rs
+ */
+use sqlite3_sys::SQLITE_MISUSE;
+
+use crate::db::SQLite;
+use crate::error::{Error, Result};
+use crate::store::Store;
+
+/// Migration written in rust.
+type MigrationFn = dyn Fn(&mut SQLite) -> Result<()>;
+
+/// One step of the schema migration.
+enum Step {
+    /// Script of SQL statements.
+    Sql(String),
+    /// Rust code run inside the migration transaction.
+    Code(Box<MigrationFn>),
+}
+
+/// Ordered list of schema migrations.
+///
+/// Version of the database schema is kept in 'PRAGMA user_version'
+/// and is equal to the number of applied migrations (0 for new database),
+/// so migrations may only be appended, never removed or reordered.
+/// Pending migrations are applied in one IMMEDIATE transaction
+/// (or in a savepoint when a transaction is already active):
+/// all of them or none. Up to date database is only read, so it may
+/// be opened read-only. Database with version higher than the number
+/// of known migrations (created by newer code) is reported as
+/// Error::DatabaseTooNew and is not changed.
+///
+/// Migration scripts run inside the transaction, so they can't contain
+/// BEGIN/COMMIT nor statements forbidden in a transaction
+/// (e.g. 'PRAGMA journal_mode=WAL', 'VACUUM').
+/// Migration closures get the connection itself (see 'code').
+///
+/// ```ignore
+/// let migrations = Migrations::new()
+///     .sql("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT)")
+///     .sql("ALTER TABLE person ADD COLUMN age INTEGER")
+///     .code(|db| {
+///         db.update("UPDATE person SET age = ? WHERE age IS NULL", params![0])?;
+///         Ok(())
+///     });
+///
+/// let mut db = SQLite::new().file("app.sqlite");
+/// db.open_and_migrate(&migrations)?;
+/// ```
+#[derive(Default)]
+pub struct Migrations {
+    steps: Vec<Step>,
+}
+
+impl Migrations {
+    pub fn new() -> Migrations {
+        Migrations::default()
+    }
+
+    /**** sql ******************************************************/
+
+    /// Appends migration executed as SQL script (see 'SQLite::execute_script').
+    pub fn sql(mut self, script: &str) -> Self {
+        self.steps.push(Step::Sql(script.into()));
+        self
+    }
+
+    /**** code *****************************************************/
+
+    /// Appends migration executed as rust code.
+    ///
+    /// The closure receives the connection while the migration
+    /// transaction (or savepoint) is active. It must neither close
+    /// the connection nor end the transaction (COMMIT, ROLLBACK,
+    /// RELEASE); when the transaction is gone after the closure,
+    /// the migration fails with SQLITE_MISUSE. Changes committed
+    /// by the closure itself can't be rolled back then.
+    pub fn code<F>(mut self, f: F) -> Self
+        where F: Fn(&mut SQLite) -> Result<()> + 'static
+    {
+        self.steps.push(Step::Code(Box::new(f)));
+        self
+    }
+
+    /**** latest ***************************************************/
+
+    /// Returns version of the schema after all migrations.
+    pub fn latest(&self) -> u32 {
+        self.steps.len() as u32
+    }
+
+    /**** apply ****************************************************/
+
+    /// Applies pending migrations to opened database
+    /// and returns number of applied migrations.
+    pub(crate) fn apply(&self, db: &mut SQLite) -> Result<u32> {
+        let latest = self.latest();
+        // nothing is written when there is nothing to do
+        match user_version(db)? {
+            version if version == latest => return Ok(0),
+            version if version > latest => return Err(Error::DatabaseTooNew { version, latest }),
+            _ => (),
+        }
+        match db.in_transaction() {
+            true => {
+                let mut sp = db.savepoint()?;
+                let count = self.run(&mut sp)?;
+                sp.release()?;
+                Ok(count)
+            }
+            _ => db.transaction(|tr| self.run(tr)),
+        }
+    }
+
+    /// Runs pending migrations and sets the new version of the schema.
+    fn run(&self, db: &mut SQLite) -> Result<u32> {
+        let latest = self.latest();
+        // read again inside the transaction, other connection
+        // could apply migrations in the meantime
+        let version = user_version(db)?;
+        if version > latest {
+            return Err(Error::DatabaseTooNew { version, latest });
+        }
+        for (idx, step) in self.steps.iter().enumerate().skip(version as usize) {
+            let result = match step {
+                Step::Sql(script) => db.execute_script(script).map(|_| ()),
+                Step::Code(f) => f(db).and_then(|_| match db.in_transaction() {
+                    true => Ok(()),
+                    _ => Err(Error::from_code(SQLITE_MISUSE)),
+                }),
+            };
+            result.map_err(|error| Error::Migration { version: idx as u32 + 1, error: Box::new(error) })?;
+        }
+        if version < latest {
+            db.exec(&format!("PRAGMA user_version = {}", latest))?;
+        }
+        Ok(latest - version)
+    }
+}
+
+/// Reads version of the database schema.
+fn user_version(db: &mut SQLite) -> Result<u32> {
+    db.select_one("PRAGMA user_version", Store::new())?.get(0)
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: prepared.rs
+ */
+use crate::error::Result;
+use crate::row::Column;
+use crate::rows::Rows;
+use crate::stmt::Statement;
+use crate::store::Params;
+use crate::types::Parameter;
+
+/// Statement prepared by SQLite::prepare for repeated execution.
+///
+/// Typical cycle is 'bind' followed by 'execute' or 'query'.
+/// Bound values are kept until next 'bind' or 'clear_bindings',
+/// so the statement may be executed again without binding.
+/// The statement can't outlive the connection and is finalized when dropped.
+pub struct PreparedStatement<'conn> {
+    stmt: Statement<'conn>,
+}
+
+impl<'conn> PreparedStatement<'conn> {
+    pub(crate) fn new(stmt: Statement<'conn>) -> PreparedStatement<'conn> {
+        PreparedStatement { stmt }
+    }
+
+    /**** bind *****************************************************/
+
+    /// Binds passed values (previous bindings are cleared).
+    /// Values borrowed with StoreRef must outlive the statement.
+    pub fn bind<P>(&mut self, args: P) -> Result<()>
+        where P: Params + 'conn
+    {
+        self.stmt.reset();
+        self.stmt.clear_bindings();
+        self.stmt.bind(&args)
+    }
+
+    /**** execute **************************************************/
+
+    /// Executes the statement with bound values and returns number
+    /// of changed rows. The statement is reset afterwards, bindings are kept.
+    pub fn execute(&mut self) -> Result<u64> {
+        self.stmt.reset();
+        let result = self.stmt.execute();
+        self.stmt.reset();
+        result
+    }
+
+    /**** query ****************************************************/
+
+    /// Executes the statement with bound values and returns
+    /// a lazy cursor over its rows. The statement is reset when
+    /// the cursor is dropped, bindings are kept.
+    pub fn query(&mut self) -> Result<Rows<'_>> {
+        self.stmt.reset();
+        Ok(Rows::new(self.stmt.borrow()))
+    }
+
+    /**** reset ****************************************************/
+
+    /// Resets the statement, so it may be executed again.
+    pub fn reset(&mut self) {
+        self.stmt.reset();
+    }
+
+    /**** clear_bindings *******************************************/
+
+    /// Sets all parameters to NULL.
+    pub fn clear_bindings(&mut self) {
+        self.stmt.clear_bindings();
+    }
+
+    /**** column_count *********************************************/
+
+    /// Returns number of columns in result rows.
+    pub fn column_count(&self) -> usize {
+        self.stmt.column_count()
+    }
+
+    /**** columns **************************************************/
+
+    /// Returns metadata of the result columns.
+    pub fn columns(&self) -> Vec<Column> {
+        self.stmt.columns().to_vec()
+    }
+
+    /**** parameters ***********************************************/
+
+    /// Returns parameters (placeholders) of the statement.
+    pub fn parameters(&self) -> Vec<Parameter> {
+        self.stmt.parameters()
+    }
+
+    /**** readonly *************************************************/
+
+    /// Returns true when the statement makes no direct changes
+    /// to the database (sqlite3_stmt_readonly).
+    pub fn readonly(&self) -> bool {
+        self.stmt.readonly()
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: row.rs
+ */
+use std::fmt;
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::stmt::Statement;
+use crate::value::{FromValue, Value, ValueRef};
+
+/// Metadata of one column of a result set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    name: String,
+    decl_type: Option<String>,
+}
+
+impl Column {
+    pub(crate) fn new(name: String, decl_type: Option<String>) -> Column {
+        Column { name, decl_type }
+    }
+
+    /// Returns name of the column (alias if used in query).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns type declared for the column in table definition
+    /// (None for expressions).
+    pub fn decl_type(&self) -> Option<&str> {
+        self.decl_type.as_deref()
+    }
+}
+
+/// One row of a result set.
+///
+/// Values are kept in column order, so columns with the same name
+/// (e.g. 'SELECT a.id, b.id') are all available by index.
+/// NULL is represented as Value::Null.
+/// Column metadata is shared by all rows of one result set.
+#[derive(Clone)]
+pub struct Row {
+    columns: Arc<[Column]>,
+    values: Vec<Value>,
+}
+
+impl Row {
+    pub(crate) fn new(columns: Arc<[Column]>, values: Vec<Value>) -> Row {
+        Row { columns, values }
+    }
+
+    /**** len ******************************************************/
+
+    /// Returns number of columns in the row.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /**** is_empty *************************************************/
+
+    /// Returns true when the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /**** columns **************************************************/
+
+    /// Returns metadata of all columns.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /**** column_name **********************************************/
+
+    /// Returns name of the column with passed index.
+    pub fn column_name(&self, idx: usize) -> Option<&str> {
+        self.columns.get(idx).map(Column::name)
+    }
+
+    /**** column_index *********************************************/
+
+    /// Returns index of the first column with passed name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.name == name)
+    }
+
+    /**** value ****************************************************/
+
+    /// Returns value of the column with passed index
+    /// (None when the index is out of range).
+    pub fn value(&self, idx: usize) -> Option<&Value> {
+        self.values.get(idx)
+    }
+
+    /**** value_by_name ********************************************/
+
+    /// Returns value of the first column with passed name
+    /// (None when there is no such column).
+    pub fn value_by_name(&self, name: &str) -> Option<&Value> {
+        self.column_index(name).map(|idx| &self.values[idx])
+    }
+
+    /**** get ******************************************************/
+
+    /// Returns value of the column (by index or name) converted to T.
+    /// Use Option<T> for nullable columns.
+    ///
+    /// ```ignore
+    /// let id: i64 = row.get("id")?;
+    /// let name: Option<String> = row.get(1)?;
+    /// ```
+    pub fn get<T: FromValue>(&self, idx: impl RowIndex) -> Result<T> {
+        let idx = idx.index(&self.columns)?;
+        T::from_value(&self.values[idx]).map_err(|error| Error::FromValue {
+            column: self.columns[idx].name.clone(),
+            error,
+        })
+    }
+
+    /**** iter *****************************************************/
+
+    /// Returns iterator over (column name, value) pairs in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.columns.iter()
+            .map(Column::name)
+            .zip(self.values.iter())
+    }
+}
+
+/// Row of a cursor borrowed from the current statement step
+/// (see 'Rows::next_ref'). Values are read without copying
+/// and are valid until the cursor moves to the next row.
+pub struct RowRef<'stmt> {
+    stmt: &'stmt Statement<'stmt>,
+    columns: &'stmt Arc<[Column]>,
+}
+
+impl<'stmt> RowRef<'stmt> {
+    pub(crate) fn new(stmt: &'stmt Statement<'stmt>, columns: &'stmt Arc<[Column]>) -> RowRef<'stmt> {
+        RowRef { stmt, columns }
+    }
+
+    /**** len ******************************************************/
+
+    /// Returns number of columns in the row.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /**** is_empty *************************************************/
+
+    /// Returns true when the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /**** columns **************************************************/
+
+    /// Returns metadata of all columns.
+    pub fn columns(&self) -> &'stmt [Column] {
+        self.columns
+    }
+
+    /**** get_ref **************************************************/
+
+    /// Returns borrowed value of the column (by index or name).
+    /// Borrowed text must be valid UTF-8, otherwise it is
+    /// Error::InvalidUtf8 (or ValueRef::Blob with TextDecoding::Raw).
+    pub fn get_ref<I: RowIndex>(&self, idx: I) -> Result<ValueRef<'stmt>> {
+        let idx = idx.index(self.columns)?;
+        self.stmt.value_ref(idx)
+    }
+
+    /**** get ******************************************************/
+
+    /// Returns value of the column converted to T (see 'Row::get').
+    /// Text and blob are copied, use 'get_ref' to avoid it.
+    pub fn get<T: FromValue>(&self, idx: impl RowIndex) -> Result<T> {
+        let idx = idx.index(self.columns)?;
+        let value = self.stmt.value_ref(idx)?.to_owned();
+        T::from_value(&value).map_err(|error| Error::FromValue {
+            column: self.columns[idx].name.clone(),
+            error,
+        })
+    }
+
+    /**** to_owned *************************************************/
+
+    /// Copies the row, so it may outlive the cursor.
+    pub fn to_owned(&self) -> Result<Row> {
+        self.stmt.fetch_row(self.columns)
+    }
+}
+
+/// Column selector accepted by Row::get (index or name).
+pub trait RowIndex {
+    /// Returns index of the selected column in passed columns.
+    fn index(&self, columns: &[Column]) -> Result<usize>;
+}
+
+impl RowIndex for usize {
+    fn index(&self, columns: &[Column]) -> Result<usize> {
+        match *self < columns.len() {
+            true => Ok(*self),
+            _ => Err(Error::InvalidColumnIndex(*self)),
+        }
+    }
+}
+
+impl RowIndex for &str {
+    fn index(&self, columns: &[Column]) -> Result<usize> {
+        columns.iter().position(|c| c.name == *self)
+            .ok_or_else(|| Error::InvalidColumnName(self.to_string()))
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                      I n d e x   T r a i t                        *
+*                                                                   *
+********************************************************************/
+
+impl Index<usize> for Row {
+    type Output = Value;
+
+    fn index(&self, idx: usize) -> &Value {
+        &self.values[idx]
+    }
+}
+
+impl Index<&str> for Row {
+    type Output = Value;
+
+    fn index(&self, name: &str) -> &Value {
+        match self.value_by_name(name) {
+            Some(value) => value,
+            None => panic!("no column named '{}'", name),
+        }
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                      D e b u g   T r a i t                        *
+*                                                                   *
+********************************************************************/
+
+impl fmt::Debug for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
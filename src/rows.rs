@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: rows.rs
+ */
+use std::sync::Arc;
+
+use sqlite3_sys::{SQLITE_DONE, SQLITE_ROW};
+
+use crate::error::Result;
+use crate::stmt::Statement;
+use crate::row::{Column, Row, RowRef};
+
+/// Lazy cursor over rows returned by a query.
+///
+/// Every call of 'next' steps the statement and fetches one row,
+/// so rows are never collected in memory. The cursor borrows
+/// the connection; the statement is reset when the cursor is dropped,
+/// so iteration may be finished at any moment.
+pub struct Rows<'a> {
+    stmt: Statement<'a>,
+    columns: Arc<[Column]>,
+    done: bool,
+}
+
+impl<'a> Rows<'a> {
+    /// Creates cursor for already bound statement.
+    pub(crate) fn new(stmt: Statement<'a>) -> Rows<'a> {
+        let columns = stmt.columns();
+        Rows { stmt, columns, done: false }
+    }
+
+    /**** columns **************************************************/
+
+    /// Returns metadata of the result columns.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /**** next_ref *************************************************/
+
+    /// Steps to the next row and returns it without copying values
+    /// (None when there are no more rows). The row borrows
+    /// the cursor, so it must be dropped before the next step.
+    ///
+    /// ```ignore
+    /// let mut rows = db.query("SELECT data FROM files", params![])?;
+    /// while let Some(row) = rows.next_ref()? {
+    ///     hasher.update(row.get_ref(0)?.as_bytes().unwrap_or_default());
+    /// }
+    /// ```
+    pub fn next_ref(&mut self) -> Result<Option<RowRef<'_>>> {
+        if self.done {
+            return Ok(None);
+        }
+        match self.stmt.step() {
+            SQLITE_ROW => Ok(Some(RowRef::new(&self.stmt, &self.columns))),
+            SQLITE_DONE => {
+                self.done = true;
+                Ok(None)
+            }
+            _ => {
+                self.done = true;
+                Err(self.stmt.error())
+            }
+        }
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                   I t e r a t o r   T r a i t                     *
+*                                                                   *
+********************************************************************/
+
+impl Iterator for Rows<'_> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.stmt.next_row(&self.columns) {
+            Ok(Some(row)) => Some(Ok(row)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
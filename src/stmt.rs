@@ -11,99 +11,163 @@
 
 extern crate sqlite3_sys;
 
-use std::ffi::{c_void, CStr, CString};
-use std::intrinsics::copy;
-use std::mem::transmute;
-use std::ptr::null_mut;
-
-use libc::{c_char, c_double, c_int};
-use sqlite3_sys::{sqlite3,
-                  sqlite3_bind_blob,
+use std::ffi::{c_void, CStr};
+use std::slice;
+use std::sync::Arc;
+
+use libc::{c_char, c_double, c_int, c_uchar};
+use sqlite3_sys::{sqlite3_bind_blob64,
                   sqlite3_bind_double,
                   sqlite3_bind_int64,
                   sqlite3_bind_null,
+                  sqlite3_bind_parameter_count,
                   sqlite3_bind_parameter_index,
-                  sqlite3_bind_text,
+                  sqlite3_bind_parameter_name,
+                  sqlite3_bind_text64,
+                  sqlite3_bind_zeroblob64,
+                  sqlite3_changes,
                   sqlite3_clear_bindings,
                   sqlite3_column_blob,
                   sqlite3_column_bytes,
                   sqlite3_column_count,
+                  sqlite3_column_decltype,
                   sqlite3_column_double,
                   sqlite3_column_int,
                   sqlite3_column_int64,
                   sqlite3_column_name,
                   sqlite3_column_text,
                   sqlite3_column_type,
-                  sqlite3_finalize,
                   sqlite3_int64,
-                  sqlite3_prepare_v2,
                   sqlite3_reset,
                   sqlite3_step,
+                  sqlite3_stmt_readonly,
                   sqlite3_stmt,
+                  sqlite3_total_changes,
+                  sqlite3_uint64,
+                  SQLITE_DONE,
+                  SQLITE_MISUSE,
                   SQLITE_OK,
-                  SQLITE_ROW};
-
-use crate::store::Store;
-use crate::types::{Row, Type};
-use crate::value::Value;
-
-use super::db::SQLite;
-
-include!("macros.inc");
+                  SQLITE_ROW,
+                  SQLITE_UTF8};
+
+use crate::error::{Error, Result};
+use crate::ffi::{from_cstr, sqlite_static, sqlite_transient, to_cstring, DbHandle, Destructor, StmtHandle};
+use crate::store::{Arg, Params};
+use crate::row::{Column, Row};
+use crate::types::{Parameter, TextDecoding, Type};
+use crate::value::{Value, ValueRef};
+
+
+/// Stmt of the Statement and what happens with it when Statement is dropped.
+enum Stmt<'conn> {
+    /// Statement owns the stmt and finalizes it.
+    Owned(StmtHandle),
+    /// Stmt is reset and its bindings are cleared (stmt from cache).
+    Cached(&'conn StmtHandle),
+    /// Stmt is only reset, bindings are kept (e.g. PreparedStatement).
+    Borrowed(&'conn StmtHandle),
+}
 
 /// Stmt object to handle prepared statement
-pub(crate) struct Statement {
-    pub(crate) stmt: *mut sqlite3_stmt,
-    db: *mut sqlite3,
+pub(crate) struct Statement<'conn> {
+    stmt: Stmt<'conn>,
+    db: &'conn DbHandle,
+    decoding: TextDecoding,
 }
 
-impl Statement {
+impl<'conn> Statement<'conn> {
     /// Creates Statement object for passed 'query'.
     /// Function will prepare a stmt.
-    pub(crate) fn for_query(db: *mut sqlite3, query: &str) -> Option<Statement> {
-        let mut stmt = Statement { stmt: null_mut(), db };
-        match stmt.prepare(query) {
-            true => Some(stmt),
-            _ => None,
-        }
+    pub(crate) fn for_query(db: &'conn DbHandle, query: &str) -> Result<Statement<'conn>> {
+        let stmt = Statement::prepare(db, query)?;
+        Ok(Statement { stmt: Stmt::Owned(stmt), db, decoding: TextDecoding::default() })
+    }
+
+    /// Prepares the first statement of 'script' starting at byte 'offset'.
+    ///
+    /// # Returns
+    /// statement (None when the rest is empty or contains only comments)
+    /// and byte offset of the text following the statement
+    pub(crate) fn for_script(db: &'conn DbHandle, script: &CStr, offset: usize) -> Result<(Option<Statement<'conn>>, usize)> {
+        let (stmt, next) = StmtHandle::prepare(db, script, offset)?;
+        let stmt = stmt.map(|stmt| Statement { stmt: Stmt::Owned(stmt), db, decoding: TextDecoding::default() });
+        Ok((stmt, next))
     }
 
-    /// Create Statement object with 'db' and 'stmt'.
+    /// Create Statement object with 'db' and 'stmt' from cache.
     /// It is nothing to do, 'stmt' is already prepared.
-    pub(crate) fn for_stmt(db: *mut sqlite3, stmt: *mut sqlite3_stmt) -> Statement {
-        Statement { stmt, db }
+    /// When dropped the stmt is reset and its bindings are cleared.
+    pub(crate) fn for_stmt(db: &'conn DbHandle, stmt: &'conn StmtHandle) -> Statement<'conn> {
+        Statement { stmt: Stmt::Cached(stmt), db, decoding: TextDecoding::default() }
+    }
+
+    /// Create Statement object borrowing 'stmt' owned by other object.
+    /// When dropped the stmt is only reset, bindings are kept.
+    pub(crate) fn borrowed(db: &'conn DbHandle, stmt: &'conn StmtHandle) -> Statement<'conn> {
+        Statement { stmt: Stmt::Borrowed(stmt), db, decoding: TextDecoding::default() }
+    }
+
+    /// Returns Statement borrowing the same stmt (see 'borrowed').
+    pub(crate) fn borrow(&self) -> Statement<'_> {
+        Statement::borrowed(self.db, self.handle()).decoding(self.decoding)
+    }
+
+    /// Sets how TEXT values which are not valid UTF-8 are read.
+    pub(crate) fn decoding(mut self, decoding: TextDecoding) -> Statement<'conn> {
+        self.decoding = decoding;
+        self
     }
 
     /**** prepare **************************************************/
 
-    /// Prepare query
-    fn prepare(&mut self, query: &str) -> bool {
-        unsafe {
-            SQLITE_OK == sqlite3_prepare_v2(
-                self.db,
-                str2ptr!(query),
-                -1,
-                &mut self.stmt,
-                std::ptr::null_mut(),
-            )
+    /// Prepare query, query without any statement is an error.
+    pub(crate) fn prepare(db: &DbHandle, query: &str) -> Result<StmtHandle> {
+        let sql = to_cstring(query)?;
+        match StmtHandle::prepare(db, &sql, 0) {
+            Ok((Some(stmt), _)) => Ok(stmt),
+            Ok((None, _)) => Err(Error::from_code(SQLITE_MISUSE).with_sql(query)),
+            Err(err) => Err(err.with_sql(query)),
         }
     }
 
+    /// Returns handle of the stmt.
+    fn handle(&self) -> &StmtHandle {
+        match &self.stmt {
+            Stmt::Owned(stmt) => stmt,
+            Stmt::Cached(stmt) | Stmt::Borrowed(stmt) => stmt,
+        }
+    }
+
+    /// Returns pointer to the stmt for sqlite3 calls.
+    #[inline]
+    fn ptr(&self) -> *mut sqlite3_stmt {
+        self.handle().as_ptr()
+    }
+
     /**** reset ****************************************************/
 
-    /// Resets prepared query
-    pub(crate) fn reset(&mut self) -> bool {
+    /// Resets prepared query (bindings are kept)
+    pub(crate) fn reset(&self) -> bool {
+        unsafe {
+            SQLITE_OK == sqlite3_reset(self.ptr())
+        }
+    }
+
+    /**** clear_bindings *******************************************/
+
+    /// Sets all parameters of prepared query to NULL
+    pub(crate) fn clear_bindings(&self) -> bool {
         unsafe {
-            SQLITE_OK == (sqlite3_reset(self.stmt) & sqlite3_clear_bindings(self.stmt))
+            SQLITE_OK == sqlite3_clear_bindings(self.ptr())
         }
     }
 
-    /**** finalize *************************************************/
+    /**** readonly *************************************************/
 
-    /// Finalize prepared query
-    pub(crate) fn finalize(&self) -> bool {
+    /// Checks whether prepared query makes no direct changes to the database
+    pub(crate) fn readonly(&self) -> bool {
         unsafe {
-            SQLITE_OK == sqlite3_finalize(self.stmt)
+            sqlite3_stmt_readonly(self.ptr()) != 0
         }
     }
 
@@ -111,14 +175,51 @@ impl Statement {
 
     /// Step to next row in result
     pub(crate) fn step(&self) -> c_int {
-        unsafe { sqlite3_step(self.stmt) }
+        unsafe { sqlite3_step(self.ptr()) }
+    }
+
+    /**** error ****************************************************/
+
+    /// Returns last error of the connection of the statement.
+    pub(crate) fn error(&self) -> Error {
+        Error::from_db(self.db.as_ptr())
+    }
+
+    /**** execute **************************************************/
+
+    /// Wykonanie zapytania do końca, zwrócone wiersze
+    /// (np. z klauzuli RETURNING) są pomijane.
+    ///
+    /// # Returns
+    /// liczba wierszy zmienionych przez INSERT, UPDATE lub DELETE
+    /// (sqlite3_changes, bez zmian wykonanych przez triggery),
+    /// 0 dla pozostałych zapytań
+    pub(crate) fn execute(&self) -> Result<u64> {
+        let db = self.db.as_ptr();
+        let before = unsafe { sqlite3_total_changes(db) };
+        loop {
+            match self.step() {
+                SQLITE_ROW => continue,
+                SQLITE_DONE => break,
+                _ => return Err(Error::from_db(db)),
+            }
+        }
+        // sqlite3_changes keeps the count of the last INSERT, UPDATE
+        // or DELETE, so it is used only when this query changed rows
+        let changes = unsafe {
+            match sqlite3_total_changes(db) == before {
+                true => 0,
+                _ => sqlite3_changes(db),
+            }
+        };
+        Ok(changes as u64)
     }
 
     /**** column_count *********************************************/
 
     /// Columns number in row in result
     pub(crate) fn column_count(&self) -> usize {
-        unsafe { sqlite3_column_count(self.stmt) as usize }
+        unsafe { sqlite3_column_count(self.ptr()) as usize }
     }
 
     /**** column_type **********************************************/
@@ -126,17 +227,47 @@ impl Statement {
     /// Returns value type in column
     pub(crate) fn column_type(&self, idx: usize) -> Type {
         unsafe {
-            let sqlite_type = sqlite3_column_type(self.stmt, idx as c_int);
+            let sqlite_type = sqlite3_column_type(self.ptr(), idx as c_int);
             Type::vtype(sqlite_type as usize)
         }
     }
 
-    /**** column_index *********************************************/
+    /**** parameter_index ******************************************/
 
-    /// Zwraca indeks kolumny o wskazanej nazwie
-    pub(crate) fn column_index(&self, column_name: &str) -> i32 {
+    /// Zwraca indeks parametru o wskazanej nazwie (z prefiksem ':', '@', '$').
+    /// Dla nazwy bez prefiksu sprawdzane są wszystkie prefiksy.
+    pub(crate) fn parameter_index(&self, name: &str) -> Option<usize> {
+        let find = |name: &str| -> Option<usize> {
+            // name with NUL byte can't be a parameter name
+            let name = to_cstring(name).ok()?;
+            let idx = unsafe {
+                sqlite3_bind_parameter_index(self.ptr(), name.as_ptr())
+            };
+            match idx {
+                0 => None,
+                _ => Some(idx as usize),
+            }
+        };
+        match name.starts_with([':', '@', '$']) {
+            true => find(name),
+            _ => [":", "@", "$"].iter()
+                .find_map(|prefix| find(&format!("{}{}", prefix, name))),
+        }
+    }
+
+    /**** parameter_count ******************************************/
+
+    /// Zwraca liczbę parametrów (największy indeks parametru)
+    pub(crate) fn parameter_count(&self) -> usize {
+        unsafe { sqlite3_bind_parameter_count(self.ptr()) as usize }
+    }
+
+    /**** parameter_name *******************************************/
+
+    /// Zwraca nazwę parametru o podanym indeksie (None dla '?')
+    pub(crate) fn parameter_name(&self, idx: usize) -> Option<String> {
         unsafe {
-            sqlite3_bind_parameter_index(self.stmt, str2ptr!(column_name))
+            from_cstr(sqlite3_bind_parameter_name(self.ptr(), idx as c_int))
         }
     }
 
@@ -145,33 +276,112 @@ impl Statement {
     /// Zwraca nazwę kolumny o podanym indeksie
     pub(crate) fn column_name(&self, idx: usize) -> String {
         unsafe {
-            let ptr = sqlite3_column_name(self.stmt, idx as c_int);
-            String::from_utf8_lossy(CStr::from_ptr(ptr).to_bytes()).into_owned()
+            from_cstr(sqlite3_column_name(self.ptr(), idx as c_int)).unwrap_or_default()
         }
     }
 
+    /**** column_decltype ******************************************/
+
+    /// Zwraca zadeklarowany typ kolumny o podanym indeksie
+    /// (None dla wyrażeń)
+    pub(crate) fn column_decltype(&self, idx: usize) -> Option<String> {
+        unsafe {
+            from_cstr(sqlite3_column_decltype(self.ptr(), idx as c_int))
+        }
+    }
+
+    /**** columns **************************************************/
+
+    /// Zwraca metadane wszystkich kolumn wyniku
+    /// (współdzielone przez wszystkie wiersze)
+    pub(crate) fn columns(&self) -> Arc<[Column]> {
+        (0..self.column_count())
+            .map(|i| Column::new(self.column_name(i), self.column_decltype(i)))
+            .collect()
+    }
+
+    /**** parameters ***********************************************/
+
+    /// Zwraca opis wszystkich parametrów zapytania (indeks i nazwa)
+    pub(crate) fn parameters(&self) -> Vec<Parameter> {
+        (1..=self.parameter_count())
+            .map(|idx| Parameter::new(idx, self.parameter_name(idx)))
+            .collect()
+    }
+
     /**** bind *****************************************************/
 
     /// Bindowanie przysłanych argumentów do spreparowanego 'stmt'
-    pub(crate) fn bind(&mut self, args: Store) -> bool {
-        for it in args.iter().enumerate() {
-            if !self.bind_at_index(it.0 + 1, it.1) {
-                return false;
+    ///
+    /// Wartości pozycyjne wiązane są kolejno od indeksu 1,
+    /// wartości nazwane według indeksu parametru o tej nazwie.
+    /// Nieznana nazwa, parametr nazwany bez wartości lub parametr
+    /// z więcej niż jedną wartością to błąd.
+    pub(crate) fn bind<P: Params>(&mut self, args: &P) -> Result<()> {
+        let count = self.parameter_count();
+        let positional = args.positional();
+        let named = args.named();
+        if positional.len() > count {
+            return Err(self.parameter_count_error(positional.len() + named.len()));
+        }
+        let mut bound = vec![false; count + 1];
+
+        for (i, v) in positional.iter().enumerate() {
+            self.bind_at_index(i + 1, v)?;
+            bound[i + 1] = true;
+        }
+        for (name, v) in named.iter() {
+            let idx = self.parameter_index(name)
+                .ok_or_else(|| Error::UnknownParameter(name.to_string()))?;
+            // the same parameter given positionally or under another name
+            if bound[idx] {
+                let name = self.parameter_name(idx).unwrap_or_else(|| format!("?{}", idx));
+                return Err(Error::DuplicateParameter(name));
+            }
+            self.bind_at_index(idx, v)?;
+            bound[idx] = true;
+        }
+
+        // every parameter must have a value
+        match bound.iter().skip(1).position(|flag| !flag) {
+            None => Ok(()),
+            Some(pos) => match self.parameter_name(pos + 1) {
+                Some(name) if !name.starts_with('?') => Err(Error::UnboundParameter(name)),
+                _ => Err(self.parameter_count_error(positional.len() + named.len())),
             }
         }
-        true
+    }
+
+    /// Błąd niezgodności liczby wartości z liczbą parametrów
+    fn parameter_count_error(&self, supplied: usize) -> Error {
+        Error::ParameterCount {
+            expected: self.parameter_count(),
+            supplied,
+            names: self.parameters().into_iter()
+                .map(|p| p.name().unwrap_or("?").to_string())
+                .collect(),
+        }
     }
 
     /**** bind_at_index ********************************************/
 
-    /// Bindowanie podanej wartości na wskazanej pozycji
-    fn bind_at_index(&self, idx: usize, v: &Value) -> bool {
-        match v {
-            Value::Null => self.bind_null(idx),
-            Value::Int(x) => self.bind_i64(idx, *x),
-            Value::Float(x) => self.bind_f64(idx, *x),
-            Value::Text(x) => self.bind_str(idx, x),
-            Value::Blob(x) => self.bind_blob(idx, x),
+    /// Bindowanie podanej wartości na wskazanej pozycji.
+    /// Wartości Copied są kopiowane przez sqlite3, wartości Borrowed
+    /// muszą istnieć dopóki stmt nie zostanie zresetowany.
+    fn bind_at_index(&self, idx: usize, v: &Arg) -> Result<()> {
+        let stat = match *v {
+            Arg::Copied(&Value::Null) | Arg::Borrowed(ValueRef::Null) => self.bind_null(idx),
+            Arg::Copied(&Value::Int(x)) | Arg::Borrowed(ValueRef::Int(x)) => self.bind_i64(idx, x),
+            Arg::Copied(&Value::Float(x)) | Arg::Borrowed(ValueRef::Float(x)) => self.bind_f64(idx, x),
+            Arg::Copied(Value::Text(x)) => self.bind_str(idx, x, sqlite_transient()),
+            Arg::Copied(Value::Blob(x)) => self.bind_blob(idx, x, sqlite_transient()),
+            Arg::Borrowed(ValueRef::Text(x)) => self.bind_str(idx, x, sqlite_static()),
+            Arg::Borrowed(ValueRef::Blob(x)) => self.bind_blob(idx, x, sqlite_static()),
+            Arg::ZeroBlob(size) => self.bind_zeroblob(idx, size),
+        };
+        match stat {
+            SQLITE_OK => Ok(()),
+            _ => Err(Error::from_db(self.db.as_ptr()).with_param(idx))
         }
     }
 
@@ -181,115 +391,132 @@ impl Statement {
     ///
     /// # Returns
     /// wektor wierszy (być może pusty)
-    pub(crate) fn fetch_result(&self) -> Option<Vec<Row>> {
-        let column_count = self.column_count();
+    pub(crate) fn fetch_result(&self) -> Result<Vec<Row>> {
+        let columns = self.columns();
         let mut result = Vec::new();
 
-        while SQLITE_ROW == self.step() {
-            let row = self.fetch_row(column_count);
+        while let Some(row) = self.next_row(&columns)? {
             if !row.is_empty() {
                 result.push(row);
             }
         }
+        Ok(result)
+    }
 
-        match !result.is_empty() {
-            true => Some(result),
-            _ => None
+    /**** next_row *************************************************/
+
+    /// Przejście do następnego wiersza wyniku i jego odczyt.
+    ///
+    /// # Returns
+    /// wiersz lub None gdy nie ma więcej wierszy
+    pub(crate) fn next_row(&self, columns: &Arc<[Column]>) -> Result<Option<Row>> {
+        match self.step() {
+            SQLITE_ROW => self.fetch_row(columns).map(Some),
+            SQLITE_DONE => Ok(None),
+            _ => Err(Error::from_db(self.db.as_ptr())),
         }
     }
 
     /**** fetch_row ************************************************/
 
-    /// Zwraca wiersz z wyniku
-    pub(crate) fn fetch_row(&self, n: usize) -> Row {
-        let mut row = Row::with_capacity(n);
+    /// Zwraca wiersz z wyniku (w kolejności kolumn)
+    pub(crate) fn fetch_row(&self, columns: &Arc<[Column]>) -> Result<Row> {
+        let values = (0..columns.len())
+            .map(|i| match self.column_type(i) {
+                Type::Null => Ok(Value::Null),
+                Type::Int64 => Ok(Value::from(self.fetch_i64(i))),
+                Type::Float64 => Ok(Value::from(self.fetch_f64(i))),
+                Type::Text => self.fetch_text(i),
+                Type::Blob => Ok(Value::from(self.fetch_blob(i))),
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        Ok(Row::new(columns.clone(), values))
+    }
 
-        for i in 0..n {
-            let name = self.column_name(i);
-            match self.column_type(i) {
-                Type::Null => {
-                    row.insert(name, None);
-                }
-                Type::Int64 => {
-                    row.insert(name, Some(Value::from(self.fetch_i64(i))));
-                }
-                Type::Float64 => {
-                    row.insert(name, Some(Value::from(self.fetch_f64(i))));
-                }
-                Type::Text => {
-                    row.insert(name, Some(Value::from(self.fetch_str(i))));
-                }
-                Type::Blob => {
-                    row.insert(name, Some(Value::from(self.fetch_blob(i))));
+    /**** fetch_text ***********************************************/
+
+    /// Odczyt wartości tekstowej zgodnie z trybem 'decoding'.
+    /// Tekst niepoprawny w UTF-8 jest zamieniany (Lossy),
+    /// zgłaszany jako błąd (Strict) lub zwracany jako blob (Raw).
+    fn fetch_text(&self, idx: usize) -> Result<Value> {
+        match String::from_utf8(self.text_bytes(idx).to_vec()) {
+            Ok(text) => Ok(Value::Text(text)),
+            Err(err) => match self.decoding {
+                TextDecoding::Lossy =>
+                    Ok(Value::Text(String::from_utf8_lossy(err.as_bytes()).into_owned())),
+                TextDecoding::Strict => Err(self.invalid_utf8(idx, err.utf8_error())),
+                TextDecoding::Raw => Ok(Value::Blob(err.into_bytes())),
+            }
+        }
+    }
+
+    /**** value_ref ************************************************/
+
+    /// Odczyt wartości kolumny bieżącego wiersza bez kopiowania.
+    /// Wartość jest ważna do następnego 'step' lub 'reset'.
+    /// Tekst niepoprawny w UTF-8 jest błędem (Lossy, Strict)
+    /// lub jest zwracany jako blob (Raw).
+    pub(crate) fn value_ref(&self, idx: usize) -> Result<ValueRef<'_>> {
+        match self.column_type(idx) {
+            Type::Null => Ok(ValueRef::Null),
+            Type::Int64 => Ok(ValueRef::Int(self.fetch_i64(idx))),
+            Type::Float64 => Ok(ValueRef::Float(self.fetch_f64(idx))),
+            Type::Blob => Ok(ValueRef::Blob(self.blob_bytes(idx))),
+            Type::Text => {
+                let bytes = self.text_bytes(idx);
+                match std::str::from_utf8(bytes) {
+                    Ok(text) => Ok(ValueRef::Text(text)),
+                    Err(_) if self.decoding == TextDecoding::Raw => Ok(ValueRef::Blob(bytes)),
+                    Err(err) => Err(self.invalid_utf8(idx, err)),
                 }
-            };
+            }
         }
-        row
+    }
+
+    fn invalid_utf8(&self, idx: usize, error: std::str::Utf8Error) -> Error {
+        Error::InvalidUtf8 { column: self.column_name(idx), error }
     }
 
     /*                       S E T T E R S                             */
 
-    fn bind_i64(&self, idx: usize, v: i64) -> bool {
+    fn bind_i64(&self, idx: usize, v: i64) -> c_int {
         unsafe {
-            match sqlite3_bind_int64(self.stmt, idx as c_int, v as sqlite3_int64) {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+            sqlite3_bind_int64(self.ptr(), idx as c_int, v as sqlite3_int64)
         }
     }
-    fn bind_f64(&self, idx: usize, v: f64) -> bool {
+    fn bind_f64(&self, idx: usize, v: f64) -> c_int {
         unsafe {
-            match sqlite3_bind_double(self.stmt, idx as c_int, v as c_double) {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+            sqlite3_bind_double(self.ptr(), idx as c_int, v as c_double)
         }
     }
-    fn bind_str(&self, idx: usize, v: &str) -> bool {
+    // size is passed as 64-bit value, so it can't wrap to negative
+    // (sqlite3 reads to NUL then); too big data is SQLITE_TOOBIG
+    fn bind_str(&self, idx: usize, v: &str, destructor: Destructor) -> c_int {
         unsafe {
             let idx = idx as c_int;
             let ptr = v.as_ptr() as *const c_char;
-            let nbytes = v.len() as c_int;
+            let nbytes = v.len() as sqlite3_uint64;
 
-            match sqlite3_bind_text(self.stmt, idx, ptr, nbytes, transmute(!0 as *const c_void)) {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+            sqlite3_bind_text64(self.ptr(), idx, ptr, nbytes, destructor, SQLITE_UTF8 as c_uchar)
         }
     }
-    fn bind_blob(&self, idx: usize, v: &Vec<u8>) -> bool {
+    fn bind_blob(&self, idx: usize, v: &[u8], destructor: Destructor) -> c_int {
         unsafe {
             let idx = idx as c_int;
             let ptr = v.as_ptr() as *const c_void;
-            let nbytes = v.len() as c_int;
+            let nbytes = v.len() as sqlite3_uint64;
 
-            match sqlite3_bind_blob(self.stmt, idx, ptr, nbytes, transmute(!0 as *const c_void)) {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+            sqlite3_bind_blob64(self.ptr(), idx, ptr, nbytes, destructor)
         }
     }
-    fn bind_null(&self, idx: usize) -> bool {
+    fn bind_zeroblob(&self, idx: usize, size: u64) -> c_int {
         unsafe {
-            match sqlite3_bind_null(self.stmt, idx as c_int) {
-                SQLITE_OK => true,
-                _ => {
-                    sql_error!(self.db);
-                    false
-                }
-            }
+            sqlite3_bind_zeroblob64(self.ptr(), idx as c_int, size)
+        }
+    }
+    fn bind_null(&self, idx: usize) -> c_int {
+        unsafe {
+            sqlite3_bind_null(self.ptr(), idx as c_int)
         }
     }
 
@@ -297,47 +524,63 @@ impl Statement {
 
     fn fetch_i64(&self, idx: usize) -> i64 {
         unsafe {
-            sqlite3_column_int64(self.stmt, idx as c_int)
+            sqlite3_column_int64(self.ptr(), idx as c_int)
         }
     }
     #[inline]
     fn fetch_int(&self, idx: usize) -> isize {
         unsafe {
-            sqlite3_column_int(self.stmt, idx as c_int) as isize
+            sqlite3_column_int(self.ptr(), idx as c_int) as isize
         }
     }
     #[inline]
     fn fetch_f64(&self, idx: usize) -> f64 {
         unsafe {
-            sqlite3_column_double(self.stmt, idx as c_int)
+            sqlite3_column_double(self.ptr(), idx as c_int)
         }
     }
     #[inline]
-    fn fetch_str(&self, idx: usize) -> String {
+    fn fetch_blob(&self, idx: usize) -> Vec<u8> {
+        self.blob_bytes(idx).to_vec()
+    }
+    #[inline]
+    fn text_bytes(&self, idx: usize) -> &[u8] {
         unsafe {
-            // sql_error!(self.db);
-            let ptr = sqlite3_column_text(self.stmt, idx as c_int);
-            String::from_utf8_lossy(CStr::from_ptr(ptr as *const c_char).to_bytes()).into_owned()
+            // size from sqlite3, text may contain NUL bytes
+            let ptr = sqlite3_column_text(self.ptr(), idx as c_int);
+            let nbytes = sqlite3_column_bytes(self.ptr(), idx as c_int) as usize;
+            match ptr.is_null() {
+                true => &[],
+                _ => slice::from_raw_parts(ptr, nbytes),
+            }
         }
     }
     #[inline]
-    fn fetch_blob(&self, idx: usize) -> Vec<u8> {
+    fn blob_bytes(&self, idx: usize) -> &[u8] {
         unsafe {
-            let nbytes = sqlite3_column_bytes(self.stmt, idx as c_int) as usize;
-            let ptr = sqlite3_column_blob(self.stmt, idx as c_int);
-            let mut data: Vec<u8> = vec![0; nbytes];
-            copy(ptr as *mut c_void, data.as_mut_ptr() as *mut c_void, nbytes);
-            data
+            // pointer first, then size (sqlite3 docs); empty blob is NULL
+            let ptr = sqlite3_column_blob(self.ptr(), idx as c_int);
+            let nbytes = sqlite3_column_bytes(self.ptr(), idx as c_int) as usize;
+            match ptr.is_null() {
+                true => &[],
+                _ => slice::from_raw_parts(ptr as *const u8, nbytes),
+            }
         }
     }
 }
 
-impl Drop for Statement {
+impl Drop for Statement<'_> {
     fn drop(&mut self) {
-        if !self.stmt.is_null() {
-            self.reset();
-            // self.finalize();
-            self.stmt = null_mut();
+        // owned stmt is finalized by its handle
+        match self.stmt {
+            Stmt::Owned(_) => (),
+            Stmt::Cached(_) => {
+                self.reset();
+                self.clear_bindings();
+            }
+            Stmt::Borrowed(_) => {
+                self.reset();
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -11,10 +11,18 @@ use core::slice::Iter;
 use chrono::NaiveDateTime;
 use crate::types::Timestamp;
 
-use crate::value::{NullValue, Value};
+use crate::value::{NullValue, Value, ValueRef};
 
+/// Values of query parameters.
+///
+/// Values added with 'add' are bound by position (to '?' placeholders),
+/// values added with 'add_named' are bound by name
+/// (':name', '@name' or '$name' placeholders).
 #[derive(Debug, Default)]
-pub struct Store(Vec<Value>);
+pub struct Store {
+    values: Vec<Value>,
+    named: Vec<(String, Value)>,
+}
 
 
 impl Store {
@@ -22,7 +30,7 @@ impl Store {
         Store::default()
     }
     pub fn with_capacity(capacity: usize) -> Store {
-        Store(Vec::with_capacity(capacity))
+        Store { values: Vec::with_capacity(capacity), named: Vec::new() }
     }
 
     /// Dodanie Value dla parametru przysłanego przez wartość.
@@ -31,28 +39,209 @@ impl Store {
         where T: ValueConvertible
     {
         let v = data.to_value();
-        self.0.push(v);
+        self.values.push(v);
+        self
+    }
+
+    /// Adds value of the named parameter.
+    /// Name may be passed with prefix (':id', '@id', '$id')
+    /// or without it ('id'), then any prefix matches.
+    pub fn add_named<T>(mut self, name: &str, data: T) -> Self
+        where T: ValueConvertible
+    {
+        self.named.push((name.into(), data.to_value()));
         self
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.values.is_empty() && self.named.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len() + self.named.len()
     }
+
+    /// Returns iterator over positional values.
     pub fn iter(&self) -> Iter<'_, Value> {
-        self.0.iter()
+        self.values.iter()
+    }
+
+    /// Returns iterator over (name, value) pairs of named values.
+    pub fn iter_named(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.named.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+/// Values of query parameters borrowed from the caller.
+///
+/// Text and blobs are bound without copying (SQLITE_STATIC),
+/// so the borrowed data must live as long as the statement uses it.
+/// The compiler ensures it: query methods tie the lifetime of
+/// the returned cursor or prepared statement to the borrowed data.
+///
+/// ```ignore
+/// let data: Vec<u8> = fs::read("image.png")?;
+/// db.insert("INSERT INTO file (name, data) VALUES (?, ?)",
+///           StoreRef::new().add("image.png").add(&data))?;
+/// ```
+#[derive(Debug, Default)]
+pub struct StoreRef<'a> {
+    values: Vec<Arg<'a>>,
+    named: Vec<(String, Arg<'a>)>,
+}
+
+impl<'a> StoreRef<'a> {
+    pub fn new() -> StoreRef<'a> {
+        StoreRef::default()
+    }
+
+    /// Adds value of the next positional parameter.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<T>(mut self, data: T) -> Self
+        where T: Into<ValueRef<'a>>
+    {
+        self.values.push(Arg::Borrowed(data.into()));
+        self
+    }
+
+    /// Adds value of the named parameter (see 'Store::add_named').
+    pub fn add_named<T>(mut self, name: &str, data: T) -> Self
+        where T: Into<ValueRef<'a>>
+    {
+        self.named.push((name.into(), Arg::Borrowed(data.into())));
+        self
+    }
+
+    /// Adds blob of 'size' zero bytes as the next positional parameter.
+    /// Space is only reserved, the content is usually written later
+    /// with incremental blob I/O.
+    pub fn add_zeroblob(mut self, size: u64) -> Self {
+        self.values.push(Arg::ZeroBlob(size));
+        self
+    }
+
+    /// Adds blob of 'size' zero bytes as value of the named parameter.
+    pub fn add_named_zeroblob(mut self, name: &str, size: u64) -> Self {
+        self.named.push((name.into(), Arg::ZeroBlob(size)));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.named.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len() + self.named.len()
     }
 }
 
+/// Values of query parameters accepted by query methods:
+/// Store (values are copied by sqlite3) or StoreRef (values are borrowed).
+pub trait Params: private::Args {}
+
+impl Params for Store {}
+impl Params for StoreRef<'_> {}
+
+pub(crate) use private::Arg;
+
+mod private {
+    use crate::value::{Value, ValueRef};
+
+    /// Value of a parameter prepared for binding.
+    #[derive(Debug, Copy, Clone)]
+    pub enum Arg<'a> {
+        /// Value copied by sqlite3 (SQLITE_TRANSIENT).
+        Copied(&'a Value),
+        /// Value used by sqlite3 without copying (SQLITE_STATIC).
+        Borrowed(ValueRef<'a>),
+        /// Blob of passed number of zero bytes.
+        ZeroBlob(u64),
+    }
+
+    /// Values of parameters in form used by Statement::bind.
+    pub trait Args {
+        /// Positional values in order of parameters.
+        fn positional(&self) -> Vec<Arg<'_>>;
+        /// Named values with names of parameters.
+        fn named(&self) -> Vec<(&str, Arg<'_>)>;
+    }
+}
+
+impl private::Args for Store {
+    fn positional(&self) -> Vec<Arg<'_>> {
+        self.values.iter().map(Arg::Copied).collect()
+    }
+    fn named(&self) -> Vec<(&str, Arg<'_>)> {
+        self.named.iter().map(|(name, value)| (name.as_str(), Arg::Copied(value))).collect()
+    }
+}
+
+impl private::Args for StoreRef<'_> {
+    fn positional(&self) -> Vec<Arg<'_>> {
+        self.values.clone()
+    }
+    fn named(&self) -> Vec<(&str, Arg<'_>)> {
+        self.named.iter().map(|(name, arg)| (name.as_str(), *arg)).collect()
+    }
+}
+
+/// Creates Store with positional or named values.
+///
+/// ```ignore
+/// db.select("SELECT * FROM person WHERE id=?", params![7])?;
+/// db.update("UPDATE person SET age=:age WHERE id=:id", params!{":id" => 7, ":age" => 42})?;
+/// ```
+#[macro_export]
+macro_rules! params {
+    () => {
+        $crate::store::Store::new()
+    };
+    ($($name:expr => $value:expr),+ $(,)?) => {
+        $crate::store::Store::new()$(.add_named($name, $value))+
+    };
+    ($($value:expr),+ $(,)?) => {
+        $crate::store::Store::new()$(.add($value))+
+    };
+}
+
 /********************************************************************
 *                                                                   *
 *             T r a i t - V a l u e C o n v e r t i b l e           *
 *                                                                   *
 ********************************************************************/
 
+/// Conversion of a rust type to Value (bound as query parameter).
+///
+/// Together with 'value::FromValue' (conversion of fetched Value
+/// back to the rust type) it allows to use own types directly
+/// in Store::add and Row::get:
+///
+/// ```ignore
+/// struct UserId(i64);
+///
+/// impl ValueConvertible for UserId {
+///     fn to_value(&self) -> Value {
+///         self.0.to_value()
+///     }
+/// }
+///
+/// impl FromValue for UserId {
+///     fn from_value(value: &Value) -> Result<UserId, FromValueError> {
+///         i64::from_value(value).map(UserId)
+///     }
+/// }
+///
+/// db.insert("INSERT INTO user (id) VALUES (?)", Store::new().add(UserId(7)))?;
+/// let id: UserId = row.get("id")?;
+/// ```
 pub trait ValueConvertible {
     fn to_value(&self) -> Value;
 }
 
+impl<T: ValueConvertible + ?Sized> ValueConvertible for &T {
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
 impl ValueConvertible for NaiveDateTime {
     fn to_value(&self) -> Value {
         (*self).into()
@@ -108,14 +297,24 @@ impl ValueConvertible for f64 {
         (*self).into()
     }
 }
-impl<'a> ValueConvertible for &'a str {
+impl ValueConvertible for bool {
     fn to_value(&self) -> Value {
-        (*self).into()
+        (*self as i64).into()
     }
 }
-impl<'a> ValueConvertible for &'a Vec<u8> {
+impl ValueConvertible for str {
     fn to_value(&self) -> Value {
-        (*self).into()
+        self.into()
+    }
+}
+impl ValueConvertible for String {
+    fn to_value(&self) -> Value {
+        self.as_str().into()
+    }
+}
+impl ValueConvertible for [u8] {
+    fn to_value(&self) -> Value {
+        Value::Blob(self.to_vec())
     }
 }
 impl ValueConvertible for Vec<u8> {
@@ -123,6 +322,19 @@ impl ValueConvertible for Vec<u8> {
         self.into()
     }
 }
+impl ValueConvertible for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+impl<T: ValueConvertible> ValueConvertible for Option<T> {
+    fn to_value(&self) -> Value {
+        match self {
+            Some(v) => v.to_value(),
+            None => Value::Null,
+        }
+    }
+}
 impl ValueConvertible for NullValue {
     fn to_value(&self) -> Value {
         Value::Null
@@ -0,0 +1,221 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: transaction.rs
+ */
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use crate::db::SQLite;
+use crate::error::Result;
+
+/// Locking mode used when a transaction begins.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum TransactionMode {
+    /// Locks are acquired on first read/write.
+    #[default]
+    Deferred,
+    /// Write lock is acquired immediately.
+    Immediate,
+    /// Exclusive lock is acquired immediately.
+    Exclusive,
+}
+
+impl TransactionMode {
+    fn begin_query(&self) -> &'static str {
+        match self {
+            TransactionMode::Deferred => "BEGIN DEFERRED",
+            TransactionMode::Immediate => "BEGIN IMMEDIATE",
+            TransactionMode::Exclusive => "BEGIN EXCLUSIVE",
+        }
+    }
+}
+
+/// Policy of repeating a transaction when the database is busy or locked.
+///
+/// Delay before n-th retry is 'initial_delay * 2^n', but not longer than 'max_delay'.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// How many times the transaction may be repeated (0 - never).
+    pub max_retries: u32,
+    /// Delay before first retry.
+    pub initial_delay: Duration,
+    /// Upper limit of delay between retries.
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Policy which never repeats the transaction.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy { max_retries: 0, ..RetryPolicy::default() }
+    }
+
+    /// Returns delay before retry number 'attempt' (counted from 0).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Guard of an opened transaction.
+///
+/// Gives access to all query methods of SQLite (exec_query, insert,
+/// select, update, ...). Changes must be committed explicitly,
+/// otherwise they are rolled back when the guard is dropped
+/// (also during panic unwinding).
+///
+/// Other methods of SQLite are available too: 'begin' and 'transaction'
+/// fail (transactions can't be nested, use 'savepoint'), 'create' fails
+/// with Error::AlreadyOpened and 'close' discards the transaction.
+pub struct Transaction<'a> {
+    db: &'a mut SQLite,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    /// Begins a transaction in passed mode.
+    pub(crate) fn new(db: &'a mut SQLite, mode: TransactionMode) -> Result<Transaction<'a>> {
+        db.exec(mode.begin_query())?;
+        Ok(Transaction { db, finished: false })
+    }
+
+    /**** commit ***************************************************/
+
+    /// Commits changes made in the transaction.
+    /// On failure the transaction is rolled back when the guard is dropped.
+    pub fn commit(mut self) -> Result<()> {
+        self.db.exec("COMMIT")?;
+        self.finished = true;
+        Ok(())
+    }
+
+    /**** rollback *************************************************/
+
+    /// Rolls back changes made in the transaction.
+    pub fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.db.exec("ROLLBACK")
+    }
+}
+
+/// Guard of a savepoint (nested transaction).
+///
+/// Can be created from SQLite, Transaction or another Savepoint,
+/// so savepoints may be nested to any depth. Changes must be released
+/// explicitly, otherwise they are rolled back to the savepoint
+/// when the guard is dropped.
+pub struct Savepoint<'a> {
+    db: &'a mut SQLite,
+    name: String,
+    finished: bool,
+}
+
+impl<'a> Savepoint<'a> {
+    /// Starts a savepoint with passed unique name.
+    pub(crate) fn new(db: &'a mut SQLite, name: String) -> Result<Savepoint<'a>> {
+        db.exec(&format!("SAVEPOINT {}", name))?;
+        Ok(Savepoint { db, name, finished: false })
+    }
+
+    /**** name *****************************************************/
+
+    /// Returns generated name of the savepoint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /**** release **************************************************/
+
+    /// Releases the savepoint keeping its changes
+    /// (they are committed when it is the outermost one).
+    pub fn release(mut self) -> Result<()> {
+        self.db.exec(&format!("RELEASE {}", self.name))?;
+        self.finished = true;
+        Ok(())
+    }
+
+    /**** rollback *************************************************/
+
+    /// Rolls back changes made after the savepoint and releases it.
+    pub fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.rollback_and_release()
+    }
+
+    fn rollback_and_release(&mut self) -> Result<()> {
+        self.db.exec(&format!("ROLLBACK TO {}", self.name))?;
+        self.db.exec(&format!("RELEASE {}", self.name))
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                      D e r e f   T r a i t                        *
+*                                                                   *
+********************************************************************/
+
+impl Deref for Transaction<'_> {
+    type Target = SQLite;
+
+    fn deref(&self) -> &SQLite {
+        self.db
+    }
+}
+
+impl DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut SQLite {
+        self.db
+    }
+}
+
+impl Deref for Savepoint<'_> {
+    type Target = SQLite;
+
+    fn deref(&self) -> &SQLite {
+        self.db
+    }
+}
+
+impl DerefMut for Savepoint<'_> {
+    fn deref_mut(&mut self) -> &mut SQLite {
+        self.db
+    }
+}
+
+/********************************************************************
+*                                                                   *
+*                       D r o p   T r a i t                         *
+*                                                                   *
+********************************************************************/
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            // Errors can't be reported from drop.
+            let _ = self.db.exec("ROLLBACK");
+        }
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            // Errors can't be reported from drop.
+            let _ = self.rollback_and_release();
+        }
+    }
+}
@@ -7,14 +7,11 @@
  * Project: rs-sqlite
  * File: types.rs
  */
-use std::collections::HashMap;
 use std::convert::From;
 use chrono::{DateTime, Local, Utc};
-use crate::value::Value;
-pub type Row = HashMap<String, Option<Value>>;
 
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Type {
     Int64 = 1,
     Float64,
@@ -36,12 +33,57 @@ impl Type {
     }
 }
 
+/// How TEXT values which are not valid UTF-8 are read
+/// (see 'SQLite::text_decoding').
+/// Valid text is always read as Value::Text, including embedded NUL bytes.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub enum TextDecoding {
+    /// Invalid sequences are replaced with U+FFFD.
+    #[default]
+    Lossy,
+    /// Invalid text is an error (Error::InvalidUtf8).
+    Strict,
+    /// Invalid text is read as Value::Blob with unchanged bytes.
+    Raw,
+}
+
+/// Description of a query parameter (placeholder).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    index: usize,
+    name: Option<String>,
+}
+
+impl Parameter {
+    pub(crate) fn new(index: usize, name: Option<String>) -> Parameter {
+        Parameter { index, name }
+    }
+    /// Returns 1-based index of the parameter.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// Returns name of the parameter with prefix (e.g. ':id', '?2'),
+    /// None for anonymous '?'.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Result of one item of SQLite::execute_batch.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BatchItem {
+    /// Rowid of the last inserted row (meaningful for INSERT).
+    pub rowid: i64,
+    /// Number of rows changed by the item.
+    pub changes: u64,
+}
+
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 pub struct Timestamp(i64);
 
 impl Timestamp {
     pub fn now() -> Timestamp {
-        let dt_utc = DateTime::<Utc>::from_utc(Local::now().naive_utc(), Utc);
+        let dt_utc = DateTime::<Utc>::from_naive_utc_and_offset(Local::now().naive_utc(), Utc);
         Timestamp(dt_utc.timestamp())
     }
     pub fn tm(v: i64) -> Timestamp {
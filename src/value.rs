@@ -7,12 +7,13 @@
  * Project: rs-sqlite
  * File: value.rs
  */
+use std::fmt;
 use crate::types::{Timestamp, Type};
 use chrono::NaiveDateTime;
 
 pub struct NullValue;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Null,
     Int(i64),
@@ -23,6 +24,9 @@ pub enum Value {
 
 
 impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
     pub fn kind(&self) -> Type {
         match self {
             Value::Null => Type::Null,
@@ -34,6 +38,147 @@ impl Value {
     }
 }
 
+/// Value of a column borrowed from the current row of a cursor
+/// (see 'Rows::next_ref'), text and blob are not copied.
+/// It is valid until the cursor moves to the next row.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ValueRef<'stmt> {
+    Null,
+    Int(i64),
+    Float(f64),
+    Text(&'stmt str),
+    Blob(&'stmt [u8]),
+}
+
+impl<'stmt> ValueRef<'stmt> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, ValueRef::Null)
+    }
+    pub fn kind(&self) -> Type {
+        match self {
+            ValueRef::Null => Type::Null,
+            ValueRef::Int(_) => Type::Int64,
+            ValueRef::Float(_) => Type::Float64,
+            ValueRef::Text(_) => Type::Text,
+            ValueRef::Blob(_) => Type::Blob
+        }
+    }
+    /// Returns integer (None for other types).
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ValueRef::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+    /// Returns float (None for other types).
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ValueRef::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+    /// Returns borrowed text (None for other types).
+    pub fn as_str(&self) -> Option<&'stmt str> {
+        match self {
+            ValueRef::Text(v) => Some(v),
+            _ => None,
+        }
+    }
+    /// Returns borrowed bytes of blob or text (None for other types).
+    pub fn as_bytes(&self) -> Option<&'stmt [u8]> {
+        match self {
+            ValueRef::Text(v) => Some(v.as_bytes()),
+            ValueRef::Blob(v) => Some(v),
+            _ => None,
+        }
+    }
+    /// Copies the value, so it may outlive the cursor.
+    pub fn to_owned(&self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Int(v) => Value::Int(*v),
+            ValueRef::Float(v) => Value::Float(*v),
+            ValueRef::Text(v) => Value::Text(v.to_string()),
+            ValueRef::Blob(v) => Value::Blob(v.to_vec()),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(v: ValueRef<'_>) -> Self {
+        v.to_owned()
+    }
+}
+
+impl From<i32> for ValueRef<'_> {
+    fn from(v: i32) -> Self {
+        ValueRef::Int(v as i64)
+    }
+}
+
+impl From<i64> for ValueRef<'_> {
+    fn from(v: i64) -> Self {
+        ValueRef::Int(v)
+    }
+}
+
+impl From<f64> for ValueRef<'_> {
+    fn from(v: f64) -> Self {
+        ValueRef::Float(v)
+    }
+}
+
+impl From<bool> for ValueRef<'_> {
+    fn from(v: bool) -> Self {
+        ValueRef::Int(v as i64)
+    }
+}
+
+impl<'a> From<&'a str> for ValueRef<'a> {
+    fn from(v: &'a str) -> Self {
+        ValueRef::Text(v)
+    }
+}
+
+impl<'a> From<&'a String> for ValueRef<'a> {
+    fn from(v: &'a String) -> Self {
+        ValueRef::Text(v)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ValueRef<'a> {
+    fn from(v: &'a [u8]) -> Self {
+        ValueRef::Blob(v)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for ValueRef<'a> {
+    fn from(v: &'a Vec<u8>) -> Self {
+        ValueRef::Blob(v)
+    }
+}
+
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    fn from(v: &'a Value) -> Self {
+        match v {
+            Value::Null => ValueRef::Null,
+            Value::Int(v) => ValueRef::Int(*v),
+            Value::Float(v) => ValueRef::Float(*v),
+            Value::Text(v) => ValueRef::Text(v),
+            Value::Blob(v) => ValueRef::Blob(v),
+        }
+    }
+}
+
+impl<'a, T: Into<ValueRef<'a>>> From<Option<T>> for ValueRef<'a> {
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => ValueRef::Null,
+        }
+    }
+}
+
 impl From<Timestamp> for Value {
     fn from(v: Timestamp) -> Self {
         Value::Int(v.value())
@@ -112,57 +257,171 @@ impl From<NullValue> for Value {
     }
 }
 
-impl From<&Value> for i64 {
-    fn from(value: &Value) -> i64 {
+/********************************************************************
+*                                                                   *
+*                  T r a i t - F r o m V a l u e                    *
+*                                                                   *
+********************************************************************/
+
+/// Error of conversion from Value to a rust type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromValueError {
+    /// Value has other type than expected (e.g. NULL read as i64).
+    InvalidType { expected: &'static str, found: Type },
+    /// Integer value doesn't fit into the target type.
+    OutOfRange { target: &'static str, value: i64 },
+    /// Value has expected type but can't be parsed (e.g. date/time text).
+    Invalid(String),
+}
+
+impl fmt::Display for FromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromValueError::InvalidType { expected, found } =>
+                write!(f, "expected {}, found {:?}", expected, found),
+            FromValueError::OutOfRange { target, value } =>
+                write!(f, "value {} out of range for {}", value, target),
+            FromValueError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FromValueError {}
+
+/// Conversion from Value (read from database) to a rust type.
+///
+/// Use Option<T> for nullable columns, NULL read as T is an error.
+/// Own types implement it together with 'store::ValueConvertible'
+/// (conversion in opposite direction), usually by delegating
+/// to the implementation of the wrapped type.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, FromValueError>;
+}
+
+fn invalid_type<T>(value: &Value) -> FromValueError {
+    FromValueError::InvalidType { expected: std::any::type_name::<T>(), found: value.kind() }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Result<i64, FromValueError> {
+        match value {
+            Value::Int(v) => Ok(*v),
+            _ => Err(invalid_type::<i64>(value))
+        }
+    }
+}
+
+/// Integers are read as i64 and narrowed with range check.
+macro_rules! from_value_integer(
+    ($($t:ty),*) => ($(
+        impl FromValue for $t {
+            fn from_value(value: &Value) -> Result<$t, FromValueError> {
+                let v = i64::from_value(value).map_err(|_| invalid_type::<$t>(value))?;
+                <$t>::try_from(v).map_err(|_| FromValueError::OutOfRange {
+                    target: stringify!($t),
+                    value: v,
+                })
+            }
+        }
+    )*);
+);
+
+from_value_integer!(i8, i16, i32, isize, u8, u16, u32, u64, usize);
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<bool, FromValueError> {
         match value {
-            Value::Int(v) => *v,
-            _ => panic!("it is not i64")
+            Value::Int(v) => Ok(*v != 0),
+            _ => Err(invalid_type::<bool>(value))
         }
     }
 }
 
-impl From<&Value> for f64 {
-    fn from(value: &Value) -> f64 {
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<f64, FromValueError> {
         match value {
-            Value::Float(v) => *v,
-            _ => panic!("it is not f64")
+            Value::Float(v) => Ok(*v),
+            Value::Int(v) => Ok(*v as f64),
+            _ => Err(invalid_type::<f64>(value))
         }
     }
 }
 
-impl From<&Value> for String {
-    fn from(value: &Value) -> String {
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> Result<f32, FromValueError> {
+        f64::from_value(value)
+            .map(|v| v as f32)
+            .map_err(|_| invalid_type::<f32>(value))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<String, FromValueError> {
         match value {
-            Value::Text(text) => text.clone(),
-            _ => panic!("it is not a string")
+            Value::Text(text) => Ok(text.clone()),
+            _ => Err(invalid_type::<String>(value))
         }
     }
 }
 
-impl From<&Value> for Vec<u8> {
-    fn from(value: &Value) -> Vec<u8> {
+/// Text is read as its UTF-8 bytes, so columns read with
+/// TextDecoding::Raw give the same type for valid and invalid text.
+impl FromValue for Vec<u8> {
+    fn from_value(value: &Value) -> Result<Vec<u8>, FromValueError> {
         match value {
-            Value::Blob(value) => value.clone(),
-            _ => panic!("it is not a vec<u8>")
+            Value::Blob(data) => Ok(data.clone()),
+            Value::Text(text) => Ok(text.as_bytes().to_vec()),
+            _ => Err(invalid_type::<Vec<u8>>(value))
         }
     }
 }
 
-impl From<&Value> for Timestamp {
-    fn from(value: &Value) -> Timestamp {
+impl FromValue for Timestamp {
+    fn from_value(value: &Value) -> Result<Timestamp, FromValueError> {
         match value {
-            Value::Int(value) => Timestamp::tm(*value),
-            _ => panic!("it is not a Timestamp(i64)")
+            Value::Int(v) => Ok(Timestamp::tm(*v)),
+            _ => Err(invalid_type::<Timestamp>(value))
         }
     }
 }
 
-impl From<&Value> for NaiveDateTime {
-    fn from(value: &Value) -> NaiveDateTime {
+impl FromValue for NaiveDateTime {
+    fn from_value(value: &Value) -> Result<NaiveDateTime, FromValueError> {
         match value {
-            Value::Text(value) => NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap(),
-            _ => panic!("it is not a NaiveDateTime"),
+            Value::Text(text) => NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                .map_err(|err| FromValueError::Invalid(format!("'{}': {}", text, err))),
+            _ => Err(invalid_type::<NaiveDateTime>(value))
         }
     }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Value, FromValueError> {
+        Ok(value.clone())
+    }
+}
 
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Option<T>, FromValueError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_value(value).map(Some),
+        }
+    }
 }
+
+/// TryFrom<&Value> for all types with FromValue.
+macro_rules! try_from_value(
+    ($($t:ty),*) => ($(
+        impl TryFrom<&Value> for $t {
+            type Error = FromValueError;
+
+            fn try_from(value: &Value) -> Result<$t, FromValueError> {
+                <$t>::from_value(value)
+            }
+        }
+    )*);
+);
+
+try_from_value!(bool, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize,
+    f32, f64, String, Vec<u8>, Timestamp, NaiveDateTime);
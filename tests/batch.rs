@@ -0,0 +1,65 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/batch.rs
+ */
+//! Tests of batch execution.
+
+mod common;
+
+use rs_sqlite::error::Error;
+use rs_sqlite::params;
+use rs_sqlite::types::BatchItem;
+
+use common::open_db;
+
+#[test]
+fn batch_execution() {
+    let mut db = open_db();
+    let items = (1..=100).map(|i| params![i, format!("row {}", i)]);
+    let result = db.execute_batch("INSERT INTO t (id, txt) VALUES (?, ?)", items).unwrap();
+    assert_eq!(result.len(), 100);
+    assert_eq!(result[99], BatchItem { rowid: 100, changes: 1 });
+
+    // duplicated key in the third item, whole batch is rolled back
+    let items = vec![params![101, "a"], params![102, "b"], params![1, "c"], params![103, "d"]];
+    match db.execute_batch("INSERT INTO t (id, txt) VALUES (?, ?)", items) {
+        Err(Error::Batch { index, error }) => {
+            assert_eq!(index, 2);
+            assert!(error.sql().is_some());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(db.select("SELECT id FROM t", params![]).unwrap().len(), 100);
+
+    let result = db.execute_batch_with("UPDATE t SET txt = ? WHERE id > ?", vec![params!["x", 50], params!["y", 90]], None).unwrap();
+    assert_eq!(result.iter().map(|item| item.changes).collect::<Vec<_>>(), vec![50, 10]);
+}
+
+#[test]
+fn batch_inside_transaction() {
+    let mut db = open_db();
+    let mut tr = db.begin().unwrap();
+    tr.insert("INSERT INTO t (id, txt) VALUES (?, ?)", params![1, "first"]).unwrap();
+    let items = vec![params![2, "a"], params![3, "b"]];
+    let result = tr.execute_batch("INSERT INTO t (id, txt) VALUES (?, ?)", items).unwrap();
+    assert_eq!(result.len(), 2);
+
+    // failed batch is rolled back to its savepoint, the transaction goes on
+    let items = vec![params![4, "c"], params![1, "d"]];
+    assert!(matches!(tr.execute_batch("INSERT INTO t (id, txt) VALUES (?, ?)", items),
+                     Err(Error::Batch { index: 1, .. })));
+    assert_eq!(tr.select("SELECT id FROM t", params![]).unwrap().len(), 3);
+    tr.commit().unwrap();
+    assert_eq!(db.select("SELECT id FROM t", params![]).unwrap().len(), 3);
+
+    // batch inside a savepoint
+    let mut sp = db.savepoint().unwrap();
+    sp.execute_batch("INSERT INTO t (id, txt) VALUES (?, ?)", vec![params![5, "e"]]).unwrap();
+    sp.rollback().unwrap();
+    assert_eq!(db.select("SELECT id FROM t", params![]).unwrap().len(), 3);
+}
@@ -0,0 +1,87 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/cache.rs
+ */
+//! Tests of the prepared statements cache.
+
+mod common;
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::params;
+
+use common::open_db;
+
+const SELECT: &str = "SELECT count(*) FROM t";
+const SELECT_ONE: &str = "SELECT 1";
+
+#[test]
+fn hits_and_misses_are_counted() {
+    let mut db = open_db().cache_capacity(4);
+    let start = db.cache_stats();
+    for _ in 0..3 {
+        db.select(SELECT, params![]).unwrap();
+    }
+    let stats = db.cache_stats();
+    assert_eq!(stats.misses - start.misses, 1);
+    assert_eq!(stats.hits - start.hits, 2);
+    assert_eq!(stats.evictions, 0);
+    assert_eq!(stats.capacity, 4);
+}
+
+#[test]
+fn least_recently_used_statement_is_evicted() {
+    let mut db = open_db().cache_capacity(2);
+    db.select(SELECT, params![]).unwrap();
+    let start = db.cache_stats();
+    // every insert is a new query, the select used between them
+    // is always more recent than the oldest insert
+    for i in 0..5 {
+        db.insert(&format!("INSERT INTO t (id) VALUES ({})", i), params![]).unwrap();
+        db.select(SELECT, params![]).unwrap();
+    }
+    let stats = db.cache_stats();
+    assert_eq!(stats.size, 2);
+    assert_eq!(stats.hits - start.hits, 5);
+    assert_eq!(stats.misses - start.misses, 5);
+    assert_eq!(stats.evictions - start.evictions, 4);
+
+    // without the re-use the select becomes the oldest one and is evicted
+    db.insert("INSERT INTO t (id) VALUES (10)", params![]).unwrap();
+    db.insert("INSERT INTO t (id) VALUES (11)", params![]).unwrap();
+    let before = db.cache_stats();
+    let row = db.select_one(SELECT, params![]).unwrap();
+    assert_eq!(row.get::<i64>(0).unwrap(), 7);
+    assert_eq!(db.cache_stats().misses - before.misses, 1);
+}
+
+#[test]
+fn cache_is_cleared() {
+    let mut db = open_db().cache_capacity(4);
+    db.select(SELECT, params![]).unwrap();
+    assert!(db.cache_stats().size > 0);
+    db.clear_cache();
+    assert_eq!(db.cache_stats().size, 0);
+    db.select(SELECT, params![]).unwrap();
+    assert_eq!(db.cache_stats().size, 1);
+}
+
+#[test]
+fn reuse_with_zero_capacity_restores_default() {
+    let mut db = SQLite::new().in_memory().cache_capacity(0);
+    db.create(vec![]).unwrap();
+    db.select(SELECT_ONE, params![]).unwrap();
+    assert_eq!(db.cache_stats().size, 0);
+
+    let mut db = SQLite::new().in_memory().cache_capacity(0).reuse_prepared();
+    db.create(vec![]).unwrap();
+    db.select(SELECT_ONE, params![]).unwrap();
+    db.select(SELECT_ONE, params![]).unwrap();
+    let stats = db.cache_stats();
+    assert!(stats.capacity > 0);
+    assert_eq!((stats.size, stats.evictions, stats.hits), (1, 0, 1));
+}
@@ -0,0 +1,34 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/common/mod.rs
+ */
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use rs_sqlite::db::SQLite;
+
+/// Opens in-memory database with table 't' used by most tests.
+pub fn open_db() -> SQLite {
+    open_db_with(vec!["CREATE TABLE t (id INTEGER PRIMARY KEY, txt TEXT, data BLOB)"])
+}
+
+/// Opens in-memory database created with passed queries.
+pub fn open_db_with(schema: Vec<&str>) -> SQLite {
+    let mut db = SQLite::new().in_memory();
+    db.create(schema).unwrap();
+    db
+}
+
+/// Returns path of a database file in the temporary directory,
+/// unique for the test process (the file is removed if exists).
+pub fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("rs-sqlite-{}-{}.sqlite", name, std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    let _ = std::fs::remove_file(&path);
+    path
+}
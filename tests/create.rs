@@ -0,0 +1,83 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/create.rs
+ */
+//! Tests of creating and opening database files.
+
+mod common;
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::error::Error;
+use rs_sqlite::params;
+use rs_sqlite::transaction::{RetryPolicy, TransactionMode};
+
+use common::temp_path;
+
+#[test]
+fn create_never_removes_existing_database() {
+    let path = &temp_path("create");
+    let schema = vec!["CREATE TABLE t (x INTEGER)", "INSERT INTO t VALUES (1)"];
+
+    let mut db = SQLite::new().file(path);
+    assert!(db.open_or_create(schema.clone()).unwrap());
+    db.insert("INSERT INTO t VALUES (?)", params![2]).unwrap();
+    db.close().unwrap();
+
+    let mut db = SQLite::new().file(path);
+    assert!(matches!(db.create(schema.clone()), Err(Error::DatabaseExists(_))));
+    assert!(!db.open_or_create(schema.clone()).unwrap());
+    assert_eq!(db.select("SELECT x FROM t", params![]).unwrap().len(), 2);
+    db.close().unwrap();
+
+    let mut db = SQLite::new().file(path);
+    db.recreate(schema.clone()).unwrap();
+    assert_eq!(db.select("SELECT x FROM t", params![]).unwrap().len(), 1);
+    db.close().unwrap();
+
+    // failed schema leaves the database empty, so it is executed again
+    std::fs::remove_file(path).unwrap();
+    let mut db = SQLite::new().file(path);
+    assert!(db.open_or_create(vec!["CREATE TABLE t (x INTEGER)", "CREATE TABLE t (y)"]).is_err());
+    assert!(db.open_or_create(schema).unwrap());
+    db.close().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn failed_create_removes_new_file() {
+    let path = &temp_path("failed-create");
+
+    let mut db = SQLite::new().file(path);
+    assert!(db.create(vec!["CREATE TABLE t (x INTEGER)", "INSERT INTO missing VALUES (1)"]).is_err());
+    assert!(!std::path::Path::new(path).exists());
+    assert!(matches!(db.select("SELECT 1", params![]), Err(Error::NotOpened)));
+
+    // nothing is left behind, so the database may be created again
+    db.create(vec!["CREATE TABLE t (x INTEGER)", "INSERT INTO t VALUES (1)"]).unwrap();
+    assert_eq!(db.select("SELECT x FROM t", params![]).unwrap().len(), 1);
+    db.close().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn existing_database_is_opened_while_other_connection_writes() {
+    let path = &temp_path("locked");
+    let schema = vec!["CREATE TABLE t (x INTEGER)"];
+
+    let mut writer = SQLite::new().file(path);
+    assert!(writer.open_or_create(schema.clone()).unwrap());
+    let tr = writer.begin_with(TransactionMode::Immediate).unwrap();
+
+    let mut db = SQLite::new().file(path).retry_policy(RetryPolicy::none());
+    assert!(!db.open_or_create(schema).unwrap());
+    assert!(db.select("SELECT x FROM t", params![]).unwrap().is_empty());
+    db.close().unwrap();
+    tr.rollback().unwrap();
+    writer.close().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
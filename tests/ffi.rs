@@ -0,0 +1,254 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/ffi.rs
+ */
+//! Integration tests, all of them cross the FFI boundary
+//! (C strings, statement ownership, handles, bound and fetched data).
+//!
+//! sqlite3 is a C library, so Miri can't execute these tests. Leaks and
+//! invalid memory accesses are caught by running them with a sanitizer:
+//!
+//! RUSTFLAGS="-Zsanitizer=address" cargo +nightly test --test ffi --target x86_64-unknown-linux-gnu
+
+mod common;
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::error::Error;
+use rs_sqlite::params;
+use rs_sqlite::store::StoreRef;
+use rs_sqlite::types::TextDecoding;
+use rs_sqlite::value::{Value, ValueRef};
+
+use common::open_db;
+
+#[test]
+fn interior_nul_in_sql_is_error() {
+    let mut db = open_db();
+    assert!(matches!(db.exec("SELECT 1;\0 DROP TABLE t"), Err(Error::Nul(_))));
+    assert!(matches!(db.select("SELECT\0 1", params![]), Err(Error::Nul(_))));
+    assert!(matches!(db.prepare("SELECT\0 1"), Err(Error::Nul(_))));
+    assert!(db.exists("SELECT 1 FROM t", params![]).is_ok());
+}
+
+#[test]
+fn interior_nul_in_path_is_error() {
+    let mut db = SQLite::new().file("/tmp/rs-sqlite\0.db");
+    assert!(matches!(db.open(false), Err(Error::Nul(_))));
+}
+
+#[test]
+fn interior_nul_in_parameter_name_is_unknown() {
+    let mut db = open_db();
+    let result = db.insert("INSERT INTO t (txt) VALUES (:txt)", params!{":t\0xt" => "a"});
+    assert!(matches!(result, Err(Error::UnknownParameter(_))));
+}
+
+#[test]
+fn failed_open_releases_handle() {
+    let mut db = SQLite::new().file("/nonexistent-dir/db.sqlite");
+    assert!(db.open(false).is_err());
+    assert!(matches!(db.exec("SELECT 1"), Err(Error::NotOpened)));
+}
+
+#[test]
+fn empty_text_and_blob_round_trip() {
+    let mut db = open_db();
+    db.insert("INSERT INTO t (txt, data) VALUES (?, ?)", params!["", Vec::<u8>::new()]).unwrap();
+    let row = db.select_one("SELECT txt, data FROM t", params![]).unwrap();
+    assert_eq!(row.get::<String>("txt").unwrap(), "");
+    assert_eq!(row.get::<Vec<u8>>("data").unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn repeated_queries_do_not_leak() {
+    let mut db = open_db();
+    for i in 0..1000 {
+        db.insert("INSERT INTO t (txt) VALUES (?)", params![format!("row {}", i)]).unwrap();
+        db.exec("UPDATE t SET data = x'00' WHERE id = 1").unwrap();
+    }
+    assert_eq!(db.select("SELECT * FROM t", params![]).unwrap().len(), 1000);
+}
+
+#[test]
+fn evicted_and_cached_statements_are_finalized() {
+    let mut db = SQLite::new().in_memory().cache_capacity(2);
+    db.create(vec![]).unwrap();
+    for i in 0..10 {
+        db.select(&format!("SELECT {}", i), params![]).unwrap();
+    }
+    let stats = db.cache_stats();
+    assert_eq!(stats.size, 2);
+    assert_eq!(stats.evictions, 8);
+    db.close().unwrap();
+    assert!(matches!(db.select("SELECT 1", params![]), Err(Error::NotOpened)));
+}
+
+#[test]
+fn query_without_statement_is_an_error() {
+    for mut db in [open_db(), open_db().reuse_prepared()] {
+        for query in ["", " -- comment"] {
+            match db.select(query, params![]) {
+                Err(Error::Sqlite { code, sql, .. }) => {
+                    assert_eq!(code, 21); // SQLITE_MISUSE
+                    assert_eq!(sql.as_deref(), Some(query));
+                }
+                other => panic!("unexpected result: {:?}", other.map(|rows| rows.len())),
+            }
+            assert!(db.prepare(query).is_err());
+        }
+    }
+}
+
+#[test]
+fn unfinished_cursor_is_reset() {
+    let mut db = open_db().reuse_prepared();
+    for i in 0..5 {
+        db.insert("INSERT INTO t (id) VALUES (?)", params![i]).unwrap();
+    }
+    let query = "SELECT id FROM t WHERE id >= ? ORDER BY id";
+    let first = db.query(query, params![1]).unwrap().next().unwrap().unwrap();
+    assert_eq!(first.get::<i64>(0).unwrap(), 1);
+    let all = db.query(query, params![0]).unwrap().count();
+    assert_eq!(all, 5);
+}
+
+#[test]
+fn connections_are_independent() {
+    let mut first = open_db();
+    {
+        let mut second = open_db();
+        second.insert("INSERT INTO t (txt) VALUES (?)", params!["x"]).unwrap();
+    }
+    // dropping one connection must not shut down the library
+    first.insert("INSERT INTO t (txt) VALUES (?)", params!["y"]).unwrap();
+    assert!(first.exists("SELECT 1 FROM t", params![]).unwrap());
+}
+
+#[test]
+fn text_with_nul_is_not_truncated() {
+    let mut db = open_db();
+    db.insert("INSERT INTO t (txt) VALUES (?)", params!["a\0b"]).unwrap();
+    let row = db.select_one("SELECT txt, length(CAST(txt AS BLOB)) AS n FROM t", params![]).unwrap();
+    assert_eq!(row.get::<String>("txt").unwrap(), "a\0b");
+    assert_eq!(row.get::<i64>("n").unwrap(), 3);
+}
+
+#[test]
+fn invalid_utf8_text_decoding() {
+    let query = "SELECT CAST(x'61ff62' AS TEXT) AS txt";
+
+    let mut db = open_db();
+    let row = db.select_one(query, params![]).unwrap();
+    assert_eq!(row.get::<String>("txt").unwrap(), "a\u{fffd}b");
+
+    let mut db = open_db().text_decoding(TextDecoding::Strict);
+    assert!(matches!(db.select_one(query, params![]), Err(Error::InvalidUtf8 { .. })));
+
+    let mut db = open_db().text_decoding(TextDecoding::Raw);
+    let row = db.select_one(query, params![]).unwrap();
+    assert_eq!(row.get::<Vec<u8>>("txt").unwrap(), vec![0x61, 0xff, 0x62]);
+}
+
+#[test]
+fn borrowed_values_of_cursor() {
+    let mut db = open_db();
+    for i in 0..3u8 {
+        db.insert("INSERT INTO t (txt, data) VALUES (?, ?)", params![format!("row {}", i), vec![i; 1024]]).unwrap();
+    }
+    let mut rows = db.query("SELECT id, txt, data FROM t ORDER BY id", params![]).unwrap();
+    let mut owned = Vec::new();
+    while let Some(row) = rows.next_ref().unwrap() {
+        let id: i64 = row.get("id").unwrap();
+        let data = row.get_ref("data").unwrap();
+        assert_eq!(data, ValueRef::Blob(&[id as u8 - 1; 1024]));
+        assert_eq!(row.get_ref(1).unwrap().as_str(), Some(format!("row {}", id - 1).as_str()));
+        owned.push((data.to_owned(), row.to_owned().unwrap()));
+    }
+    assert!(rows.next_ref().unwrap().is_none());
+    assert_eq!(owned.len(), 3);
+    assert_eq!(owned[2].0, Value::Blob(vec![2; 1024]));
+    assert_eq!(owned[2].1.get::<String>("txt").unwrap(), "row 2");
+}
+
+#[test]
+fn borrowed_parameters() {
+    let mut db = open_db().reuse_prepared();
+    let text = String::from("borrowed");
+    let data = vec![7u8; 64 * 1024];
+    let id = db.insert("INSERT INTO t (txt, data) VALUES (?, ?)", StoreRef::new().add(&text).add(&data)).unwrap();
+    db.insert("INSERT INTO t (txt, data) VALUES (:txt, :data)",
+              StoreRef::new().add_named("txt", "zero").add_named_zeroblob("data", 16)).unwrap();
+
+    let rows = db.select("SELECT txt, data FROM t WHERE id >= ? ORDER BY id", StoreRef::new().add(id)).unwrap();
+    assert_eq!(rows[0].get::<String>("txt").unwrap(), "borrowed");
+    assert_eq!(rows[0].get::<Vec<u8>>("data").unwrap(), data);
+    assert_eq!(rows[1].get::<Vec<u8>>("data").unwrap(), vec![0; 16]);
+
+    let key = String::from("borrowed");
+    let mut rows = db.query("SELECT id FROM t WHERE txt = ?", StoreRef::new().add(&key)).unwrap();
+    assert_eq!(rows.next().unwrap().unwrap().get::<i64>(0).unwrap(), id);
+}
+
+#[test]
+fn too_big_borrowed_blob_is_an_error() {
+    let mut db = open_db();
+    // length over i32::MAX must not wrap; pages of the buffer are never touched
+    let data = vec![0u8; i32::MAX as usize + 1];
+    match db.insert("INSERT INTO t (data) VALUES (?)", StoreRef::new().add(&data[..])) {
+        Err(Error::Sqlite { code, param, .. }) => assert_eq!((code, param), (18, Some(1))), // SQLITE_TOOBIG
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn borrowed_parameters_of_prepared_statement() {
+    let mut db = open_db();
+    let names: Vec<String> = (0..100).map(|i| format!("name {}", i)).collect();
+    {
+        let mut stmt = db.prepare("INSERT INTO t (txt) VALUES (?)").unwrap();
+        for name in &names {
+            stmt.bind(StoreRef::new().add(name)).unwrap();
+            stmt.execute().unwrap();
+        }
+    }
+    let row = db.select_one("SELECT count(*) AS n, max(txt) AS last FROM t", params![]).unwrap();
+    assert_eq!(row.get::<i64>("n").unwrap(), 100);
+    assert_eq!(row.get::<String>("last").unwrap(), "name 99");
+}
+
+#[test]
+fn incremental_blob_io() {
+    let mut db = open_db();
+    let source: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let id = db.insert("INSERT INTO t (data) VALUES (?)", StoreRef::new().add_zeroblob(source.len() as u64)).unwrap();
+
+    let mut blob = db.open_blob("t", "data", id, false).unwrap();
+    assert_eq!(blob.len(), source.len());
+    io::copy(&mut &source[..], &mut blob).unwrap();
+    assert!(blob.write_all(&[1]).is_err());
+    blob.close().unwrap();
+
+    let mut blob = db.open_blob("t", "data", id, true).unwrap();
+    let mut target = Vec::new();
+    blob.read_to_end(&mut target).unwrap();
+    assert_eq!(target, source);
+
+    let mut tail = [0u8; 4];
+    assert_eq!(blob.seek(SeekFrom::End(-4)).unwrap(), source.len() as u64 - 4);
+    blob.read_exact(&mut tail).unwrap();
+    assert_eq!(tail, source[source.len() - 4..]);
+    assert!(blob.seek(SeekFrom::Current(-(source.len() as i64) - 1)).is_err());
+    blob.rewind().unwrap();
+    assert!(blob.write(&[1]).is_err());
+    drop(blob);
+
+    assert!(db.open_blob("t", "nothing", id, true).is_err());
+    assert!(db.open_blob("t", "data", id + 1, true).is_err());
+}
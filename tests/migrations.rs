@@ -0,0 +1,121 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/migrations.rs
+ */
+//! Tests of schema migrations.
+
+mod common;
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::error::Error;
+use rs_sqlite::migrations::Migrations;
+use rs_sqlite::params;
+
+use common::temp_path;
+
+#[test]
+fn schema_migrations() {
+    let migrations = || Migrations::new()
+        .sql("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT);\nINSERT INTO person (name) VALUES ('Luke');")
+        .sql("ALTER TABLE person ADD COLUMN age INTEGER")
+        .code(|db| {
+            db.update("UPDATE person SET age = ? WHERE age IS NULL", params![19])?;
+            Ok(())
+        });
+    let mut db = SQLite::new().in_memory();
+    assert_eq!(db.open_and_migrate(&migrations()).unwrap(), 3);
+    assert_eq!(db.migrate(&migrations()).unwrap(), 0);
+    let row = db.select_one("SELECT name, age FROM person", params![]).unwrap();
+    assert_eq!(row.get::<i64>("age").unwrap(), 19);
+
+    // failing migration rolls back all pending ones
+    let failing = migrations()
+        .sql("CREATE TABLE pet (name TEXT)")
+        .sql("INSERT INTO nothing VALUES (1)");
+    match db.migrate(&failing) {
+        Err(Error::Migration { version, .. }) => assert_eq!(version, 5),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(!db.exists("SELECT 1 FROM sqlite_master WHERE name = 'pet'", params![]).unwrap());
+
+    // database migrated by newer code
+    let newer = migrations().sql("CREATE TABLE pet (name TEXT)");
+    assert_eq!(db.migrate(&newer).unwrap(), 1);
+    match db.migrate(&migrations()) {
+        Err(Error::DatabaseTooNew { version, latest }) => assert_eq!((version, latest), (4, 3)),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn migrations_inside_transaction() {
+    let migrations = Migrations::new()
+        .sql("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT)")
+        .code(|db| {
+            db.insert("INSERT INTO person (name) VALUES (?)", params!["Luke"])?;
+            Ok(())
+        });
+    let mut db = SQLite::new().in_memory();
+    db.create(vec![]).unwrap();
+
+    let mut tr = db.begin().unwrap();
+    assert_eq!(tr.migrate(&migrations).unwrap(), 2);
+    assert_eq!(tr.migrate(&migrations).unwrap(), 0);
+    tr.rollback().unwrap();
+    assert!(!db.exists("SELECT 1 FROM sqlite_master WHERE name = 'person'", params![]).unwrap());
+
+    let mut tr = db.begin().unwrap();
+    assert_eq!(tr.migrate(&migrations).unwrap(), 2);
+    tr.commit().unwrap();
+    assert_eq!(db.select("SELECT * FROM person", params![]).unwrap().len(), 1);
+}
+
+#[test]
+fn up_to_date_database_is_not_written() {
+    let path = &temp_path("migrations");
+    let migrations = Migrations::new()
+        .sql("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT)");
+
+    let mut db = SQLite::new().file(path);
+    assert_eq!(db.open_and_migrate(&migrations).unwrap(), 1);
+    db.close().unwrap();
+
+    // read-only database must work when there is nothing to migrate
+    let mut db = SQLite::new().file(path);
+    db.open(true).unwrap();
+    assert_eq!(db.migrate(&migrations).unwrap(), 0);
+    let newer = Migrations::new()
+        .sql("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT)")
+        .sql("CREATE TABLE pet (name TEXT)");
+    assert!(db.migrate(&newer).is_err());
+    db.close().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn migration_must_not_end_transaction() {
+    let base = || Migrations::new().sql("CREATE TABLE person (id INTEGER PRIMARY KEY)");
+    let mut db = SQLite::new().in_memory();
+    db.create(vec![]).unwrap();
+
+    let committing = base().code(|db| db.exec("COMMIT"));
+    match db.migrate(&committing) {
+        Err(Error::Migration { version, error }) => {
+            assert_eq!(version, 2);
+            assert!(matches!(*error, Error::Sqlite { code: 21, .. })); // SQLITE_MISUSE
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // the table committed by the closure stays
+    assert!(db.exists("SELECT 1 FROM sqlite_master WHERE name = 'person'", params![]).unwrap());
+
+    let mut db = SQLite::new().in_memory();
+    db.create(vec![]).unwrap();
+    let closing = base().code(|db| db.close());
+    assert!(matches!(db.migrate(&closing), Err(Error::Migration { version: 2, .. })));
+}
@@ -0,0 +1,93 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/params.rs
+ */
+//! Tests of binding query parameters.
+
+mod common;
+
+use rs_sqlite::error::Error;
+use rs_sqlite::params;
+use rs_sqlite::store::Store;
+
+use common::open_db;
+
+#[test]
+fn parameters_of_query() {
+    let mut db = open_db();
+    let params = db.parameters("SELECT ?, :a, @b, $c, ?7").unwrap();
+    let described: Vec<(usize, Option<&str>)> = params.iter()
+        .map(|p| (p.index(), p.name()))
+        .collect();
+    assert_eq!(described, vec![
+        (1, None), (2, Some(":a")), (3, Some("@b")), (4, Some("$c")),
+        (5, None), (6, None), (7, Some("?7")),
+    ]);
+    assert!(db.parameters("SELECT 1").unwrap().is_empty());
+}
+
+#[test]
+fn named_parameter_without_prefix() {
+    let mut db = open_db();
+    for query in ["SELECT :a", "SELECT @a", "SELECT $a"] {
+        let row = db.select_one(query, Store::new().add_named("a", 5)).unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 5);
+    }
+    let row = db.select_one("SELECT :a, @b", params!{"b" => 2, ":a" => 1}).unwrap();
+    assert_eq!(row.get::<i64>(0).unwrap(), 1);
+    assert_eq!(row.get::<i64>(1).unwrap(), 2);
+}
+
+#[test]
+fn unknown_parameter() {
+    let mut db = open_db();
+    let err = db.select("SELECT :a", params!{":b" => 1}).unwrap_err();
+    assert!(matches!(err, Error::UnknownParameter(name) if name == ":b"));
+    let err = db.select("SELECT ?", params!{"a" => 1}).unwrap_err();
+    assert!(matches!(err, Error::UnknownParameter(name) if name == "a"));
+}
+
+#[test]
+fn unbound_parameter() {
+    let mut db = open_db();
+    let err = db.select("SELECT :a, :b", params!{":a" => 1}).unwrap_err();
+    assert!(matches!(err, Error::UnboundParameter(name) if name == ":b"));
+}
+
+#[test]
+fn parameter_count_mismatch() {
+    let mut db = open_db();
+    let err = db.select("SELECT ?, ?", params![1]).unwrap_err();
+    match err {
+        Error::ParameterCount { expected, supplied, names } => {
+            assert_eq!((expected, supplied), (2, 1));
+            assert_eq!(names, vec!["?", "?"]);
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+    let err = db.select("SELECT ?, :a", params![1, 2, 3]).unwrap_err();
+    match err {
+        Error::ParameterCount { expected, supplied, names } => {
+            assert_eq!((expected, supplied), (2, 3));
+            assert_eq!(names, vec!["?", ":a"]);
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn parameter_bound_twice() {
+    let mut db = open_db();
+    let err = db.select("SELECT :a", Store::new().add(1).add_named(":a", 2)).unwrap_err();
+    assert!(matches!(err, Error::DuplicateParameter(name) if name == ":a"));
+    let err = db.select("SELECT :a", Store::new().add_named(":a", 1).add_named("a", 2)).unwrap_err();
+    assert!(matches!(err, Error::DuplicateParameter(name) if name == ":a"));
+    // positional values may still fill named placeholders
+    let row = db.select_one("SELECT ?, :a", Store::new().add(1).add(2)).unwrap();
+    assert_eq!(row.get::<i64>(1).unwrap(), 2);
+}
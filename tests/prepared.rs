@@ -0,0 +1,76 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/prepared.rs
+ */
+//! Tests of prepared statements.
+
+mod common;
+
+use rs_sqlite::params;
+use rs_sqlite::value::Value;
+
+use common::open_db;
+
+#[test]
+fn bind_and_execute() {
+    let mut db = open_db();
+    {
+        let mut stmt = db.prepare("INSERT INTO t (id, txt) VALUES (?, ?)").unwrap();
+        for i in 1..=3 {
+            stmt.bind(params![i, format!("row {}", i)]).unwrap();
+            assert_eq!(stmt.execute().unwrap(), 1);
+        }
+    }
+    assert_eq!(db.select("SELECT * FROM t", params![]).unwrap().len(), 3);
+}
+
+#[test]
+fn query_with_kept_bindings() {
+    let mut db = open_db();
+    for i in 1..=5 {
+        db.insert("INSERT INTO t (id) VALUES (?)", params![i]).unwrap();
+    }
+    let mut stmt = db.prepare("SELECT id FROM t WHERE id > ? ORDER BY id").unwrap();
+    stmt.bind(params![2]).unwrap();
+    let first: Vec<i64> = stmt.query().unwrap().map(|row| row.unwrap().get(0).unwrap()).collect();
+    // bindings are kept, the statement runs again with the same value
+    let second: Vec<i64> = stmt.query().unwrap().map(|row| row.unwrap().get(0).unwrap()).collect();
+    assert_eq!(first, vec![3, 4, 5]);
+    assert_eq!(second, first);
+
+    // unfinished cursor and reset
+    assert_eq!(stmt.query().unwrap().next().unwrap().unwrap().get::<i64>(0).unwrap(), 3);
+    stmt.reset();
+    assert_eq!(stmt.query().unwrap().count(), 3);
+}
+
+#[test]
+fn cleared_bindings_are_null() {
+    let db = open_db();
+    let mut stmt = db.prepare("SELECT ? IS NULL, ?").unwrap();
+    stmt.bind(params![1, "x"]).unwrap();
+    let row = stmt.query().unwrap().next().unwrap().unwrap();
+    assert!(!row.get::<bool>(0).unwrap());
+    stmt.clear_bindings();
+    let row = stmt.query().unwrap().next().unwrap().unwrap();
+    assert!(row.get::<bool>(0).unwrap());
+    assert_eq!(row.value(1), Some(&Value::Null));
+}
+
+#[test]
+fn metadata() {
+    let db = open_db();
+    let stmt = db.prepare("SELECT id, txt AS name FROM t WHERE id = :id").unwrap();
+    assert_eq!(stmt.column_count(), 2);
+    let names: Vec<String> = stmt.columns().iter().map(|c| c.name().to_string()).collect();
+    assert_eq!(names, vec!["id", "name"]);
+    assert_eq!(stmt.columns()[0].decl_type(), Some("INTEGER"));
+    assert_eq!(stmt.parameters()[0].name(), Some(":id"));
+    assert!(stmt.readonly());
+    assert!(!db.prepare("DELETE FROM t").unwrap().readonly());
+}
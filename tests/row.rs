@@ -0,0 +1,68 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/row.rs
+ */
+//! Tests of rows (column order, duplicated names, metadata).
+
+mod common;
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::store::Store;
+use rs_sqlite::value::Value;
+
+use common::open_db_with;
+
+fn open_db() -> SQLite {
+    open_db_with(vec![
+        "CREATE TABLE a (id INTEGER PRIMARY KEY, name TEXT)",
+        "CREATE TABLE b (id INTEGER PRIMARY KEY, a_id INTEGER)",
+        "INSERT INTO a VALUES (1, 'first')",
+        "INSERT INTO b VALUES (10, 1)",
+    ])
+}
+
+#[test]
+fn duplicated_column_names_are_kept() {
+    let mut db = open_db();
+    let rows = db.select("SELECT a.id, b.id FROM a JOIN b ON b.a_id = a.id", Store::new()).unwrap();
+    let row = &rows[0];
+    assert_eq!(row.len(), 2);
+    assert_eq!(row.column_name(0), Some("id"));
+    assert_eq!(row.column_name(1), Some("id"));
+    assert_eq!(row.value(0), Some(&Value::Int(1)));
+    assert_eq!(row.value(1), Some(&Value::Int(10)));
+    assert_eq!(row[1], Value::Int(10));
+    // name gives the first of duplicated columns
+    assert_eq!(row.value_by_name("id"), Some(&Value::Int(1)));
+    assert_eq!(row.column_index("id"), Some(0));
+}
+
+#[test]
+fn column_order_is_preserved() {
+    let mut db = open_db();
+    let rows = db.select("SELECT name, b.a_id AS z, a.id AS m, 5 AS a FROM a JOIN b", Store::new()).unwrap();
+    let row = &rows[0];
+    let names: Vec<&str> = row.columns().iter().map(|c| c.name()).collect();
+    assert_eq!(names, vec!["name", "z", "m", "a"]);
+    assert_eq!(row.columns()[0].decl_type(), Some("TEXT"));
+    assert_eq!(row.columns()[3].decl_type(), None);
+    assert_eq!(row.value(4), None);
+    assert_eq!(row.value_by_name("missing"), None);
+}
+
+#[test]
+fn iteration_over_names_and_values() {
+    let mut db = open_db();
+    let rows = db.select("SELECT name, id, NULL AS empty FROM a", Store::new()).unwrap();
+    let pairs: Vec<(&str, &Value)> = rows[0].iter().collect();
+    assert_eq!(pairs, vec![
+        ("name", &Value::Text("first".into())),
+        ("id", &Value::Int(1)),
+        ("empty", &Value::Null),
+    ]);
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/script.rs
+ */
+//! Tests of script execution.
+
+mod common;
+
+use rs_sqlite::error::Error;
+use rs_sqlite::params;
+
+use common::open_db;
+
+#[test]
+fn script_execution() {
+    let mut db = open_db();
+    let script = "-- schema\nCREATE TABLE a (x INTEGER);\n/* data */ INSERT INTO a VALUES (1);;\nSELECT * FROM a;\n-- end";
+    assert_eq!(db.execute_script(script).unwrap(), 3);
+
+    let script = "INSERT INTO a VALUES (2);\n\n  INSERT INTO b VALUES (3);\nINSERT INTO a VALUES (4);";
+    match db.execute_script_with(script, Some(Default::default())) {
+        Err(Error::Script { ordinal, offset, line, error }) => {
+            assert_eq!((ordinal, offset, line), (2, 29, 3));
+            assert_eq!(error.sql(), Some("INSERT INTO b VALUES (3);"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(db.select("SELECT * FROM a", params![]).unwrap().len(), 1);
+
+    let script = "INSERT INTO a VALUES (2);\nINSERT INTO a VALUES ('x') RETURNING x;\nINSERT INTO a SELECT 1/0 WHERE raise(ABORT, 'stop');";
+    match db.execute_script(script) {
+        Err(Error::Script { ordinal, line, .. }) => assert_eq!((ordinal, line), (3, 3)),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(db.select("SELECT * FROM a", params![]).unwrap().len(), 3);
+}
+
+#[test]
+fn sql_of_failing_statement() {
+    let mut db = open_db();
+    // ';' inside of the literal and the trigger body doesn't end the statement
+    let script = "CREATE TABLE a (x TEXT);\nINSERT INTO b VALUES ('a;b');\nSELECT 1;";
+    match db.execute_script(script) {
+        Err(Error::Script { ordinal, error, .. }) => {
+            assert_eq!(ordinal, 2);
+            assert_eq!(error.sql(), Some("INSERT INTO b VALUES ('a;b');"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let script = "CREATE TRIGGER tr AFTER INSERT ON a BEGIN DELETE FROM a WHERE; END;\nSELECT 1;";
+    match db.execute_script(script) {
+        Err(Error::Script { ordinal, error, .. }) => {
+            assert_eq!(ordinal, 1);
+            assert_eq!(error.sql(), Some("CREATE TRIGGER tr AFTER INSERT ON a BEGIN DELETE FROM a WHERE; END;"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // the last statement without ';'
+    match db.execute_script("SELECT 1;\nSELECT * FROM missing") {
+        Err(Error::Script { error, .. }) => assert_eq!(error.sql(), Some("SELECT * FROM missing")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn script_inside_transaction() {
+    let mut db = open_db();
+    let mut tr = db.begin().unwrap();
+    tr.insert("INSERT INTO t (id) VALUES (?)", params![1]).unwrap();
+    let script = "INSERT INTO t (id) VALUES (2);\nINSERT INTO t (id) VALUES (3);";
+    assert_eq!(tr.execute_script_with(script, Some(Default::default())).unwrap(), 2);
+
+    // failed script is rolled back to its savepoint, the transaction goes on
+    let script = "INSERT INTO t (id) VALUES (4);\nINSERT INTO t (id) VALUES (1);";
+    assert!(matches!(tr.execute_script_with(script, Some(Default::default())),
+                     Err(Error::Script { ordinal: 2, .. })));
+    tr.commit().unwrap();
+    assert_eq!(db.select("SELECT id FROM t", params![]).unwrap().len(), 3);
+}
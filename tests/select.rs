@@ -0,0 +1,62 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/select.rs
+ */
+//! Tests of select methods.
+
+mod common;
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::error::Error;
+use rs_sqlite::store::Store;
+
+use common::open_db_with;
+
+fn open_db() -> SQLite {
+    open_db_with(vec![
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, txt TEXT)",
+        "INSERT INTO t (id, txt) VALUES (1, 'a'), (2, 'b'), (3, 'b')",
+    ])
+}
+
+#[test]
+fn select_empty_result_is_empty_vector() {
+    let mut db = open_db();
+    let rows = db.select("SELECT * FROM t WHERE id > ?", Store::new().add(10)).unwrap();
+    assert!(rows.is_empty());
+    assert_eq!(db.select("SELECT * FROM t", Store::new()).unwrap().len(), 3);
+}
+
+#[test]
+fn select_one_requires_exactly_one_row() {
+    let mut db = open_db();
+    let row = db.select_one("SELECT txt FROM t WHERE id = ?", Store::new().add(1)).unwrap();
+    assert_eq!(row.get::<String>("txt").unwrap(), "a");
+    assert!(matches!(db.select_one("SELECT * FROM t WHERE id = ?", Store::new().add(10)),
+                     Err(Error::QueryReturnedNoRows)));
+    assert!(matches!(db.select_one("SELECT * FROM t WHERE txt = ?", Store::new().add("b")),
+                     Err(Error::QueryReturnedMoreThanOneRow)));
+}
+
+#[test]
+fn select_optional_allows_no_row() {
+    let mut db = open_db();
+    let row = db.select_optional("SELECT id FROM t WHERE txt = ?", Store::new().add("a")).unwrap();
+    assert_eq!(row.unwrap().get::<i64>("id").unwrap(), 1);
+    assert!(db.select_optional("SELECT * FROM t WHERE id = ?", Store::new().add(10)).unwrap().is_none());
+    assert!(matches!(db.select_optional("SELECT * FROM t WHERE txt = ?", Store::new().add("b")),
+                     Err(Error::QueryReturnedMoreThanOneRow)));
+}
+
+#[test]
+fn exists_checks_for_any_row() {
+    let mut db = open_db();
+    assert!(db.exists("SELECT 1 FROM t WHERE txt = ?", Store::new().add("b")).unwrap());
+    assert!(!db.exists("SELECT 1 FROM t WHERE txt = ?", Store::new().add("c")).unwrap());
+    assert!(matches!(db.exists("SELECT 1 FROM nothing", Store::new()), Err(Error::Sqlite { .. })));
+}
@@ -0,0 +1,221 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/transaction.rs
+ */
+//! Tests of transactions and savepoints.
+
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rs_sqlite::db::SQLite;
+use rs_sqlite::error::Error;
+use rs_sqlite::store::Store;
+use rs_sqlite::transaction::{RetryPolicy, TransactionMode};
+
+use common::{open_db, temp_path};
+
+fn ids(db: &mut SQLite) -> Vec<i64> {
+    db.select("SELECT id FROM t ORDER BY id", Store::new()).unwrap()
+        .iter()
+        .map(|row| row.get(0).unwrap())
+        .collect()
+}
+
+#[test]
+fn commit_keeps_changes() {
+    let mut db = open_db();
+    let mut tr = db.begin().unwrap();
+    tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1)).unwrap();
+    tr.commit().unwrap();
+    assert_eq!(ids(&mut db), vec![1]);
+}
+
+#[test]
+fn drop_and_rollback_discard_changes() {
+    let mut db = open_db();
+    {
+        let mut tr = db.begin().unwrap();
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1)).unwrap();
+    }
+    let mut tr = db.begin().unwrap();
+    tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(2)).unwrap();
+    tr.rollback().unwrap();
+    assert!(ids(&mut db).is_empty());
+}
+
+#[test]
+fn panic_rolls_back() {
+    let mut db = open_db();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut tr = db.begin().unwrap();
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1)).unwrap();
+        panic!("failure inside transaction");
+    }));
+    assert!(result.is_err());
+    assert!(ids(&mut db).is_empty());
+    // connection is usable, no transaction is left open
+    db.begin().unwrap().commit().unwrap();
+}
+
+#[test]
+fn transaction_modes_take_right_locks() {
+    let path = temp_path("modes");
+    let mut first = SQLite::new().file(&path);
+    first.create(vec!["CREATE TABLE t (id INTEGER PRIMARY KEY, txt TEXT)"]).unwrap();
+    let mut second = SQLite::new().file(&path);
+    second.open(false).unwrap();
+
+    // DEFERRED takes no lock before the first access
+    let tr = first.begin_with(TransactionMode::Deferred).unwrap();
+    second.begin_with(TransactionMode::Immediate).unwrap().commit().unwrap();
+    drop(tr);
+
+    // IMMEDIATE takes write lock, others can still read
+    let tr = first.begin_with(TransactionMode::Immediate).unwrap();
+    assert!(second.begin_with(TransactionMode::Immediate).err().unwrap().is_busy());
+    assert!(second.select("SELECT * FROM t", Store::new()).is_ok());
+    drop(tr);
+
+    // EXCLUSIVE blocks also readers
+    let tr = first.begin_with(TransactionMode::Exclusive).unwrap();
+    assert!(second.select("SELECT * FROM t", Store::new()).err().unwrap().is_busy());
+    drop(tr);
+    assert!(second.select("SELECT * FROM t", Store::new()).is_ok());
+
+    drop((first, second));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn connection_methods_on_transaction_guard() {
+    let path = temp_path("guard");
+    let mut db = SQLite::new().file(&path);
+    db.create(vec!["CREATE TABLE t (id INTEGER PRIMARY KEY, txt TEXT)"]).unwrap();
+    {
+        let mut tr = db.begin().unwrap();
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1)).unwrap();
+
+        // transactions can't be nested (savepoints can)
+        assert!(matches!(tr.begin(), Err(Error::Sqlite { .. })));
+        assert!(matches!(tr.transaction(|_| Ok(())), Err(Error::Sqlite { .. })));
+        assert!(matches!(tr.create(vec![]), Err(Error::AlreadyOpened)));
+        assert_eq!(ids(&mut tr), vec![1]);
+
+        // closing the connection discards the transaction
+        tr.close().unwrap();
+        assert!(matches!(tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(2)), Err(Error::NotOpened)));
+    }
+    db.open(false).unwrap();
+    assert!(ids(&mut db).is_empty());
+    db.close().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn nested_savepoints() {
+    let mut db = open_db();
+    let mut tr = db.begin().unwrap();
+    tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1)).unwrap();
+    {
+        let mut outer = tr.savepoint().unwrap();
+        outer.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(2)).unwrap();
+        {
+            let mut inner = outer.savepoint().unwrap();
+            inner.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(3)).unwrap();
+            let mut innermost = inner.savepoint().unwrap();
+            innermost.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(4)).unwrap();
+            innermost.release().unwrap();
+            assert_eq!(ids(&mut inner), vec![1, 2, 3, 4]);
+            // inner is dropped, rows 3 and 4 are rolled back
+        }
+        assert_eq!(ids(&mut outer), vec![1, 2]);
+        outer.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(5)).unwrap();
+        outer.release().unwrap();
+    }
+    tr.commit().unwrap();
+    assert_eq!(ids(&mut db), vec![1, 2, 5]);
+
+    // savepoint of the connection works as a transaction
+    let mut sp = db.savepoint().unwrap();
+    sp.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(6)).unwrap();
+    let mut nested = sp.savepoint().unwrap();
+    nested.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(7)).unwrap();
+    nested.rollback().unwrap();
+    sp.release().unwrap();
+    assert_eq!(ids(&mut db), vec![1, 2, 5, 6]);
+}
+
+#[test]
+fn busy_transaction_is_retried() {
+    let path = temp_path("retry");
+    SQLite::new().file(&path).create(vec!["CREATE TABLE t (id INTEGER PRIMARY KEY, txt TEXT)"]).unwrap();
+    let policy = RetryPolicy {
+        max_retries: 5,
+        initial_delay: Duration::from_millis(5),
+        max_delay: Duration::from_millis(50),
+    };
+    let mut db = SQLite::new().file(&path).retry_policy(policy);
+    db.open(false).unwrap();
+
+    // other connection holds write lock during the whole retry policy
+    let mut other = SQLite::new().file(&path);
+    other.open(false).unwrap();
+    let tr = other.begin_with(TransactionMode::Immediate).unwrap();
+    let mut calls = 0;
+    let start = Instant::now();
+    let err = db.transaction(|tr| {
+        calls += 1;
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1))
+    }).unwrap_err();
+    assert!(err.is_busy());
+    assert_eq!(calls, 0);
+    // delays before retries: 5 + 10 + 20 + 40 + 50 ms
+    assert!(start.elapsed() >= Duration::from_millis(125));
+    drop(tr);
+
+    // lock is released by other thread while retrying
+    let (locked_tx, locked_rx) = mpsc::channel();
+    let other_path = path.clone();
+    let holder = thread::spawn(move || {
+        let mut other = SQLite::new().file(&other_path);
+        other.open(false).unwrap();
+        let tr = other.begin_with(TransactionMode::Immediate).unwrap();
+        locked_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        tr.commit().unwrap();
+    });
+    locked_rx.recv().unwrap();
+    let id = db.transaction(|tr| {
+        calls += 1;
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(2))
+    }).unwrap();
+    holder.join().unwrap();
+    assert_eq!((id, calls), (2, 1));
+    assert_eq!(ids(&mut db), vec![2]);
+
+    drop((db, other));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn closure_error_rolls_back_without_retry() {
+    let mut db = open_db();
+    let mut calls = 0;
+    let result: Result<(), Error> = db.transaction(|tr| {
+        calls += 1;
+        tr.insert("INSERT INTO t (id) VALUES (?)", Store::new().add(1))?;
+        Err(Error::Io(std::io::ErrorKind::Other.into()))
+    });
+    assert!(matches!(result, Err(Error::Io(_))));
+    assert_eq!(calls, 1);
+    assert!(ids(&mut db).is_empty());
+}
@@ -0,0 +1,86 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/value.rs
+ */
+//! Tests of conversions between rust types and values.
+
+mod common;
+
+use rs_sqlite::error::Error;
+use rs_sqlite::store::{Store, ValueConvertible};
+use rs_sqlite::types::Type;
+use rs_sqlite::value::{FromValue, FromValueError, Value};
+
+use common::open_db;
+
+#[test]
+fn typed_getters() {
+    let mut db = open_db();
+    let row = db.select_one("SELECT 300 AS big, NULL AS empty, 7 AS seven, 2.5 AS real", Store::new()).unwrap();
+
+    // narrowing is checked
+    assert_eq!(row.get::<i32>("big").unwrap(), 300);
+    match row.get::<u8>("big") {
+        Err(Error::FromValue { column, error: FromValueError::OutOfRange { target, value } }) => {
+            assert_eq!((column.as_str(), target, value), ("big", "u8", 300));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // NULL can't be read as i64, error names the column
+    match row.get::<i64>("empty") {
+        Err(Error::FromValue { column, error: FromValueError::InvalidType { found, .. } }) => {
+            assert_eq!((column.as_str(), found), ("empty", Type::Null));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // Option for nullable columns
+    assert_eq!(row.get::<Option<i64>>("empty").unwrap(), None);
+    assert_eq!(row.get::<Option<i64>>("seven").unwrap(), Some(7));
+    // integer is widened to float, not the other way
+    assert_eq!(row.get::<f64>("seven").unwrap(), 7.0);
+    assert_eq!(row.get::<f64>("real").unwrap(), 2.5);
+    assert!(row.get::<i64>("real").is_err());
+}
+
+#[derive(Debug, PartialEq)]
+struct UserId(i64);
+
+impl ValueConvertible for UserId {
+    fn to_value(&self) -> Value {
+        self.0.to_value()
+    }
+}
+
+impl FromValue for UserId {
+    fn from_value(value: &Value) -> Result<UserId, FromValueError> {
+        i64::from_value(value).map(UserId)
+    }
+}
+
+#[test]
+fn own_type_round_trip() {
+    let mut db = open_db();
+    db.insert("INSERT INTO t (id, txt) VALUES (?, ?)", Store::new().add(UserId(42)).add("x")).unwrap();
+    let row = db.select_one("SELECT id FROM t WHERE id = ?", Store::new().add(UserId(42))).unwrap();
+    assert_eq!(row.get::<UserId>("id").unwrap(), UserId(42));
+    assert_eq!(row.get::<Option<UserId>>(0).unwrap(), Some(UserId(42)));
+}
+
+#[test]
+fn none_is_bound_as_null() {
+    let mut db = open_db();
+    db.insert("INSERT INTO t (id, txt) VALUES (?, ?)", Store::new().add(1).add(None::<i64>)).unwrap();
+    db.insert("INSERT INTO t (id, txt) VALUES (?, ?)", Store::new().add(2).add(Some("x"))).unwrap();
+    let rows = db.select("SELECT txt FROM t WHERE txt IS NULL", Store::new()).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].value(0), Some(&Value::Null));
+    assert!(rows[0].value(0).unwrap().is_null());
+    assert_eq!(rows[0].get::<Option<String>>("txt").unwrap(), None);
+    let row = db.select_one("SELECT txt FROM t WHERE id = ?", Store::new().add(2)).unwrap();
+    assert_eq!(row.get::<Option<String>>("txt").unwrap(), Some("x".into()));
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright (C) 2023 Piotr Pszczółkowski
+ * Licence: GNU v2
+ *
+ * E-mail: piotr@beesoft.pl
+ *
+ * Project: rs-sqlite
+ * File: tests/write.rs
+ */
+//! Tests of write methods (changed rows, RETURNING clause).
+
+mod common;
+
+use rs_sqlite::params;
+
+use common::open_db;
+
+#[test]
+fn changed_rows() {
+    let mut db = open_db();
+    for i in 0..5 {
+        db.insert("INSERT INTO t (id) VALUES (?)", params![i]).unwrap();
+    }
+    assert_eq!(db.update("UPDATE t SET txt = ? WHERE id < ?", params!["x", 3]).unwrap(), 3);
+    assert_eq!(db.update("UPDATE t SET txt = ? WHERE id = ?", params!["x", 10]).unwrap(), 0);
+    assert_eq!(db.delete("DELETE FROM t WHERE id >= ?", params![1]).unwrap(), 4);
+    assert_eq!(db.total_changes(), 12);
+}
+
+#[test]
+fn queries_without_changes_report_zero() {
+    let mut db = open_db();
+    for i in 0..5 {
+        db.insert("INSERT INTO t (id) VALUES (?)", params![i]).unwrap();
+    }
+    assert_eq!(db.update("UPDATE t SET txt = ?", params!["x"]).unwrap(), 5);
+    // count of the previous UPDATE must not leak to other queries
+    assert_eq!(db.exec_query("CREATE TABLE u (x)", params![]).unwrap(), 0);
+    assert_eq!(db.exec_query("SELECT * FROM t", params![]).unwrap(), 0);
+    let items = db.execute_batch("CREATE TABLE v (x)", vec![params![]]).unwrap();
+    assert_eq!(items[0].changes, 0);
+}
+
+#[test]
+fn returning_clause() {
+    let mut db = open_db();
+    db.exec("CREATE TABLE k (code TEXT PRIMARY KEY, created TEXT DEFAULT 'now') WITHOUT ROWID").unwrap();
+    let rows = db.insert_returning("INSERT INTO k (code) VALUES (?), (?) RETURNING code, created", params!["a", "b"]).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].get::<String>("code").unwrap(), "b");
+    assert_eq!(rows[1].get::<String>("created").unwrap(), "now");
+
+    assert_eq!(db.exec_query("INSERT INTO k (code) VALUES (?) RETURNING code", params!["c"]).unwrap(), 1);
+    let rows = db.update_returning("UPDATE k SET created = ? WHERE code <> ? RETURNING code", params!["later", "a"]).unwrap();
+    assert_eq!(rows.len(), 2);
+    let rows = db.delete_returning("DELETE FROM k WHERE created = ? RETURNING code", params!["later"]).unwrap();
+    let mut codes: Vec<String> = rows.iter().map(|r| r.get(0).unwrap()).collect();
+    codes.sort();
+    assert_eq!(codes, vec!["b", "c"]);
+    assert_eq!(db.delete("DELETE FROM k RETURNING code", params![]).unwrap(), 1);
+}